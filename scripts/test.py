@@ -37,7 +37,7 @@ args = parser.parse_args()
 
 # A class to encapsulate the check directives in a file.
 class CheckFile:
-    regex_dir = re.compile(r'^\s*//\s*(CHECK[^:]*):\s+(.+)$')
+    regex_dir = re.compile(r'^\s*(?://|--)\s*(CHECK[^:]*):\s+(.+)$')
     ansi_escape = re.compile(r'(?:\x1B[@-_]|[\x80-\x9F])[0-?]*[ -/]*[@-~]')
 
     def __init__(self, checks, input):
@@ -161,9 +161,9 @@ if args.verbose:
 
 # A class to encapsulate the execution and checking of a single test.
 class TestCase(object):
-    regex_ignore = re.compile(r'^\s*//\s*IGNORE\b', flags=re.MULTILINE)
-    regex_fail   = re.compile(r'^\s*//\s*FAIL\b', flags=re.MULTILINE)
-    regex_run    = re.compile(r'^\s*//\s*RUN:\s+(.+)$', flags=re.MULTILINE)
+    regex_ignore = re.compile(r'^\s*(?://|--)\s*IGNORE\b', flags=re.MULTILINE)
+    regex_fail   = re.compile(r'^\s*(?://|--)\s*FAIL\b', flags=re.MULTILINE)
+    regex_run    = re.compile(r'^\s*(?://|--)\s*RUN:\s+(.+)$', flags=re.MULTILINE)
 
     def __init__(self, name, path):
         self.name = name
@@ -47,6 +47,12 @@ fn main() {
                 .long("trace-scoreboard")
                 .global(true),
         )
+        .arg(
+            Arg::with_name("trace_elab")
+                .long("trace-elab")
+                .help("Print the parameters of every module as it is elaborated")
+                .global(true),
+        )
         .arg(
             Arg::with_name("verbosity-opts")
                 .short("V")
@@ -66,6 +72,7 @@ fn main() {
                     "insts",
                     "func-args",
                     "call-args",
+                    "params",
                 ])
                 .global(true),
         )
@@ -107,6 +114,11 @@ fn main() {
                 .long("emit-pkgs")
                 .help("Dump VHDL packages for debugging"),
         )
+        .arg(
+            Arg::with_name("dump_scopes")
+                .long("dump-scopes")
+                .help("Dump the VHDL scopes of every design unit for debugging"),
+        )
         .arg(
             Arg::with_name("opt-level")
                 .short("O")
@@ -135,6 +147,19 @@ fn main() {
                 .takes_value(true)
                 .number_of_values(1),
         )
+        .arg(
+            Arg::with_name("max-inst-depth")
+                .long("max-inst-depth")
+                .value_name("DEPTH")
+                .help("Maximum depth of nested instantiations")
+                .takes_value(true)
+                .number_of_values(1)
+                .validator(|s| {
+                    s.parse::<usize>()
+                        .map(|_| ())
+                        .map_err(|_| format!("`{}` is not a valid depth", s))
+                }),
+        )
         .arg(
             Arg::with_name("output")
                 .short("o")
@@ -167,6 +192,8 @@ fn main() {
     // Configure the session.
     let mut session = Session::new();
     session.opts.trace_scoreboard = matches.is_present("trace_scoreboard");
+    session.opts.trace_elab = matches.is_present("trace_elab");
+    session.opts.dump_scopes = matches.is_present("dump_scopes");
     for v in matches
         .values_of("verbosity-opts")
         .into_iter()
@@ -184,6 +211,7 @@ fn main() {
             "insts" => Verbosity::INSTS,
             "func-args" => Verbosity::FUNC_ARGS,
             "call-args" => Verbosity::CALL_ARGS,
+            "params" => Verbosity::PARAMS,
             _ => unreachable!(),
         };
     }
@@ -307,7 +335,7 @@ fn score(sess: &Session, matches: &ArgMatches) {
         println!("{:#99?}", asts);
     }
 
-    if matches.is_present("emit_pkgs") {
+    if matches.is_present("emit_pkgs") || sess.opts.dump_scopes {
         vhdl::debug::emit_pkgs(
             sess,
             asts.iter()
@@ -317,6 +345,9 @@ fn score(sess: &Session, matches: &ArgMatches) {
                 })
                 .collect(),
         );
+        if sess.failed() {
+            std::process::exit(1);
+        }
     }
 
     // Stop processing if requested.
@@ -329,6 +360,9 @@ fn score(sess: &Session, matches: &ArgMatches) {
     let sb = ScoreBoard::new(&arenas);
     let vhdl_sb = vhdl::score::ScoreBoard::new(&arenas.vhdl);
     let svlog_sb = svlog::GlobalContext::new(&sess, &svlog_arenas);
+    if let Some(depth) = matches.value_of("max-inst-depth") {
+        svlog_sb.set_inst_depth_limit(depth.parse().unwrap());
+    }
 
     // Elaborate the requested entities or modules.
     {
@@ -71,6 +71,10 @@ pub struct SessionOptions {
     pub ignore_duplicate_defs: bool,
     /// Print a trace of scoreboard invocations for debugging purposes.
     pub trace_scoreboard: bool,
+    /// Print the parameters of every module as it is elaborated.
+    pub trace_elab: bool,
+    /// Print the VHDL scopes of every design unit after name resolution.
+    pub dump_scopes: bool,
     /// The verbosity options.
     pub verbosity: Verbosity,
     /// The optimization level.
@@ -94,5 +98,6 @@ bitflags! {
         const INSTS         = 1 << 8;
         const FUNC_ARGS     = 1 << 9;
         const CALL_ARGS     = 1 << 10;
+        const PARAMS        = 1 << 11;
     }
 }
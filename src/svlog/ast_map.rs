@@ -147,6 +147,16 @@ impl<'a> AstNode<'a> {
         }
     }
 
+    /// Check whether this is a `localparam` declaration.
+    ///
+    /// Local parameters cannot be overridden by an instantiation or defparam.
+    pub fn is_local_param(&self) -> bool {
+        match *self {
+            AstNode::TypeParam(decl, _) | AstNode::ValueParam(decl, _) => decl.local,
+            _ => false,
+        }
+    }
+
     /// Create an `AstNode` from an `AllNode`.
     pub fn from_all(all: ast::AllNode<'a>) -> Box<dyn Iterator<Item = AstNode<'a>> + 'a> {
         use crate::ast::AllNode;
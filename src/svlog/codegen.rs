@@ -65,6 +65,11 @@ struct Tables<'gcx> {
     module_signatures: HashMap<NodeEnvId, (llhd::ir::UnitName, llhd::ir::Signature)>,
     interned_types: HashMap<&'gcx UnpackedType<'gcx>, Result<HybridType>>,
     function_defs: HashMap<NodeEnvId, Result<Rc<EmittedFunction>>>,
+    /// The instantiations currently being emitted, outermost first.
+    inst_stack: Vec<NodeId>,
+    /// The hierarchical path of the module currently being emitted, starting
+    /// with the name of the top-level module.
+    inst_path: Vec<Name>,
 }
 
 impl<'gcx, C> Deref for CodeGenerator<'gcx, C> {
@@ -94,7 +99,16 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
 
     /// Emit the code for a module and all its dependent modules.
     pub fn emit_module(&mut self, id: NodeId) -> Result<Rc<EmittedModule<'gcx>>> {
-        self.emit_module_with_env(id, self.default_param_env())
+        let name = match self.hir_of(id)? {
+            HirNode::Module(m) => m.name.value,
+            _ => panic!("expected {:?} to be a module", id),
+        };
+        self.check_module_params(id)?;
+        self.tables.inst_path = vec![name];
+        let env = self.apply_param_overrides(id, &[name], self.default_param_env())?;
+        let result = self.emit_module_with_env(id, env);
+        self.warn_unmatched_param_overrides();
+        result
     }
 
     /// Emit the code for a module and all its dependent modules.
@@ -111,6 +125,16 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             _ => panic!("expected {:?} to be a module", id),
         };
         info!("Emit module `{}` with {:?}", hir.name, env);
+        if self.sess().opts.trace_elab {
+            self.emit(
+                DiagBuilder2::note(format!(
+                    "elaborating module `{}` with parameters: {}",
+                    hir.name,
+                    self.param_env_to_string(env)
+                ))
+                .span(hir.name.span),
+            );
+        }
 
         // Emit detailed port information if requested.
         if self.sess().has_verbosity(Verbosity::PORTS) {
@@ -680,87 +704,6 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         panic!("cannot map `{}` to LLHD", ty);
     }
 
-    /// Execute the initialization step of a generate loop.
-    fn execute_genvar_init(&mut self, id: NodeId, env: ParamEnv) -> Result<ParamEnv> {
-        let hir = self.hir_of(id)?;
-        match hir {
-            HirNode::GenvarDecl(_) => Ok(env),
-            HirNode::Stmt(stmt) => match stmt.kind {
-                hir::StmtKind::Assign {
-                    lhs,
-                    rhs,
-                    kind: hir::AssignKind::Block(ast::AssignOp::Identity),
-                } => {
-                    let target_id = self.resolve_node(lhs, env)?;
-                    let init_value = self.constant_value_of(rhs, env);
-                    let mut env_data = self.param_env_data(env).clone();
-                    env_data.set_value(target_id, init_value);
-                    Ok(self.intern_param_env(env_data))
-                }
-                _ => unreachable!(),
-            },
-            _ => unreachable!(),
-        }
-    }
-
-    /// Execute the iteration step of a generate loop.
-    fn execute_genvar_step(&mut self, id: NodeId, env: ParamEnv) -> Result<ParamEnv> {
-        let hir = self.hir_of(id)?;
-        let mut env_data = self.param_env_data(env).clone();
-        let next = match hir {
-            HirNode::Expr(expr) => match expr.kind {
-                hir::ExprKind::Unary(op, target_id) => {
-                    let target_id = self.resolve_node(target_id, env)?;
-                    let current_value = self.constant_value_of(target_id, env);
-                    let next_value = match current_value.kind {
-                        ValueKind::Int(ref v, ..) => match op {
-                            hir::UnaryOp::PostInc | hir::UnaryOp::PreInc => Some(v + 1),
-                            hir::UnaryOp::PostDec | hir::UnaryOp::PreDec => Some(v - 1),
-                            _ => None,
-                        }
-                        .map(|v| value::make_int(current_value.ty, v)),
-                        _ => unreachable!(),
-                    };
-                    next_value.map(|v| (target_id, self.intern_value(v)))
-                }
-                hir::ExprKind::Assign { .. } => {
-                    let mir = self.mir_rvalue(id, env);
-                    match mir.kind {
-                        mir::RvalueKind::Error => return Err(()),
-                        mir::RvalueKind::Assignment { lvalue, rvalue, .. } => {
-                            let target_id = match lvalue.kind {
-                                mir::LvalueKind::Error => return Err(()),
-                                mir::LvalueKind::Genvar(id) => id,
-                                _ => unreachable!(),
-                            };
-                            let next_value = self.const_mir_rvalue(Ref(rvalue));
-                            Some((target_id, next_value))
-                        }
-                        _ => unreachable!(),
-                    }
-                }
-                _ => None,
-            },
-            _ => None,
-        };
-        match next {
-            Some((target_id, next_value)) => {
-                env_data.set_value(target_id, next_value);
-                return Ok(self.intern_param_env(env_data));
-            }
-            None => {
-                self.emit(
-                    DiagBuilder2::error(format!(
-                        "{} is not a valid genvar iteration step",
-                        hir.desc_full()
-                    ))
-                    .span(hir.human_span()),
-                );
-                Err(())
-            }
-        }
-    }
-
     /// Emit the code for a function or task.
     pub fn emit_function(&mut self, id: NodeId, env: ParamEnv) -> Result<Rc<EmittedFunction>> {
         if let Some(x) = self.tables.function_defs.get(&id.env(env)) {
@@ -1008,6 +951,59 @@ where
         self.interned_rvalues.clear();
     }
 
+    /// Emit an instantiation of a module.
+    ///
+    /// Instantiates `target_module` in the environment `inner_env`, with the
+    /// ports connected as described by `inst`. Instance arrays call this once
+    /// per element.
+    fn emit_module_inst(
+        &mut self,
+        inst_id: NodeId,
+        inst: &InstDetails<'gcx>,
+        target_module: &'gcx hir::Module<'gcx>,
+        name: &str,
+        inner_env: ParamEnv,
+    ) -> Result<()> {
+        // Emit the instantiated module, making sure we do not recur
+        // indefinitely through a cyclic instantiation.
+        self.check_inst_depth(inst.hir.ast.name.span, &self.tables.inst_stack, inner_env)?;
+        self.tables.inst_stack.push(inst_id);
+        self.tables.inst_path.push(inst.hir.ast.name.value);
+        let target = self
+            .apply_param_overrides(target_module.id, &self.tables.inst_path, inner_env)
+            .and_then(|env| self.emit_module_with_env(target_module.id, env));
+        self.tables.inst_path.pop();
+        self.tables.inst_stack.pop();
+        let target = target?;
+
+        // Prepare the port assignments.
+        let (inputs, outputs) = self.emit_port_connections(
+            target_module.ports_new,
+            inst,
+            &target.ports.inputs,
+            &target.ports.outputs,
+        )?;
+
+        // Instantiate the module.
+        let ext_unit = self.builder.add_extern(
+            self.into.unit(target.unit).name().clone(),
+            self.into.unit(target.unit).sig().clone(),
+        );
+        self.builder.ins().inst(
+            ext_unit,
+            inputs.iter().map(|x| x.0).collect(),
+            outputs.iter().map(|x| x.0).collect(),
+        );
+        circt::llhd::InstanceOp::new(
+            self.mlir_builder,
+            &self.unique_names.add(name),
+            &target.mlir_symbol,
+            inputs.iter().map(|x| x.1),
+            outputs.iter().map(|x| x.1),
+        );
+        Ok(())
+    }
+
     /// Emit the code for the contents of a module.
     fn emit_module_block(
         &mut self,
@@ -1144,41 +1140,31 @@ where
                 _ => continue,
             };
 
-            // Emit the instantiated module.
-            let target = self.emit_module_with_env(target_module.id, inst.inner_env)?;
-
-            // Prepare the port assignments.
-            let (inputs, outputs) = self.emit_port_connections(
-                target_module.ports_new,
-                inst.as_ref(),
-                &target.ports.inputs,
-                &target.ports.outputs,
-            )?;
-
-            // Instantiate the module.
-            let ext_unit = self.builder.add_extern(
-                self.into.unit(target.unit).name().clone(),
-                self.into.unit(target.unit).sig().clone(),
-            );
-            if !inst.hir.ast.dims.is_empty() {
-                bug_span!(
-                    inst.hir.ast.span(),
-                    self.cx,
-                    "instance arrays of modules not supported"
+            // Instance arrays instantiate the module once per element. The port
+            // connections would have to be sliced per element, which is not
+            // supported yet.
+            if !inst.hir.ast.dims.is_empty() && !inst.ports.0.is_empty() {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "unsupported: port connections of instance array `{}`",
+                        inst.hir.name
+                    ))
+                    .span(inst.hir.ast.span())
+                    .add_note("Only instance arrays without port connections are supported"),
                 );
+                return Err(());
+            }
+            let elements = if inst.hir.ast.dims.is_empty() {
+                vec![(inst.hir.name.value.to_string(), inst.inner_env)]
+            } else {
+                crate::inst_details::inst_array_envs(self.cx, &inst, env)?
+                    .into_iter()
+                    .map(|(index, env)| (format!("{}_{}", inst.hir.name.value, index), env))
+                    .collect()
+            };
+            for (name, inner_env) in elements {
+                self.emit_module_inst(inst_id, &inst, target_module, &name, inner_env)?;
             }
-            self.builder.ins().inst(
-                ext_unit,
-                inputs.iter().map(|x| x.0).collect(),
-                outputs.iter().map(|x| x.0).collect(),
-            );
-            circt::llhd::InstanceOp::new(
-                self.mlir_builder,
-                &self.unique_names.add(&inst.hir.name.value.to_string()),
-                &target.mlir_symbol,
-                inputs.iter().map(|x| x.1),
-                outputs.iter().map(|x| x.1),
-            );
         }
 
         // Emit generate blocks.
@@ -1194,13 +1180,10 @@ where
                     ref main_body,
                     ref else_body,
                 } => {
-                    let k = self.constant_value_of(cond, env);
-                    if k.is_false() {
-                        if let Some(else_body) = else_body {
-                            self.emit_module_block(id, env, else_body, name_prefix)?;
-                        }
-                    } else {
+                    if self.eval_generate_condition(cond, env)? {
                         self.emit_module_block(id, env, main_body, name_prefix)?;
+                    } else if let Some(else_body) = else_body {
+                        self.emit_module_block(id, env, else_body, name_prefix)?;
                     }
                 }
                 hir::GenKind::For {
@@ -1465,6 +1448,13 @@ where
                     );
                 }
             }
+            ValueKind::Unbounded => {
+                self.emit(
+                    DiagBuilder2::error("unbounded value `$` cannot be emitted as hardware")
+                        .span(span),
+                );
+                Err(())
+            }
             ValueKind::Error => Err(()),
             _ => panic!(
                 "invalid combination of type `{}` and value {:#?}",
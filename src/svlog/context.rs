@@ -22,18 +22,20 @@
 
 use crate::crate_prelude::*;
 use crate::{
-    ast::{self, Visitor},
+    ast::{self, AcceptVisitor, Visitor},
     ast_map::{AstMap, AstNode},
     common::{arenas::Alloc, arenas::TypedArena, Session},
     func_args::FuncArgList,
     hir::{self, HirNode},
+    param_env::ParamOverride,
     port_list::PortList,
-    resolver::Scope,
+    resolver::{InstTarget, Scope},
     value::{Value, ValueData, ValueKind},
-    ParamEnv, ParamEnvData, QueryDatabase, QueryStorage,
+    ParamEnv, ParamEnvBinding, ParamEnvData, ParamEnvSource, ParamEnvStats, QueryDatabase,
+    QueryStorage, ResolvedParamValue,
 };
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     collections::{BTreeSet, HashMap, HashSet},
 };
 
@@ -65,12 +67,21 @@ pub struct GlobalContext<'gcx> {
     node_id_to_span: RefCell<HashMap<NodeId, Span>>,
     /// The tables.
     tables: GlobalTables<'gcx>,
+    /// The buffers that currently collect emitted diagnostics. See
+    /// `Context::with_diag_buffer`.
+    diag_buffers: RefCell<Vec<Vec<DiagBuilder2>>>,
+    /// The maximum depth of nested instantiations. See
+    /// `Context::inst_depth_limit`.
+    inst_depth_limit: Cell<usize>,
 }
 
+/// The default maximum depth of nested instantiations.
+pub const DEFAULT_INST_DEPTH_LIMIT: usize = 1000;
+
 impl<'gcx> GlobalContext<'gcx> {
     /// Create a new global context.
     pub fn new(sess: &'gcx Session, arena: &'gcx GlobalArenas<'gcx>) -> Self {
-        GlobalContext {
+        let gcx = GlobalContext {
             sess,
             arena,
             storage: Default::default(),
@@ -83,7 +94,15 @@ impl<'gcx> GlobalContext<'gcx> {
             imports: Default::default(),
             node_id_to_span: Default::default(),
             tables: Default::default(),
-        }
+            diag_buffers: Default::default(),
+            inst_depth_limit: Cell::new(DEFAULT_INST_DEPTH_LIMIT),
+        };
+
+        // Intern the empty parameter environment first, such that it ends up
+        // with the id reserved for `ParamEnv::EMPTY`.
+        let empty = gcx.intern_param_env(ParamEnvData::default());
+        assert_eq!(empty, ParamEnv::EMPTY);
+        gcx
     }
 
     /// Add an AST root to the context for processing.
@@ -182,6 +201,23 @@ impl<'gcx> GlobalContext<'gcx> {
     pub fn imports(&self) -> impl Iterator<Item = NodeId> {
         self.imports.borrow().clone().into_iter()
     }
+
+    /// Change the maximum depth of nested instantiations.
+    ///
+    /// Defaults to [`DEFAULT_INST_DEPTH_LIMIT`].
+    pub fn set_inst_depth_limit(&self, limit: usize) {
+        self.inst_depth_limit.set(limit);
+    }
+
+    /// Pass a diagnostic on to the innermost diagnostic buffer, or the session
+    /// if no buffer is active.
+    fn forward_diag(&self, diag: DiagBuilder2) {
+        if let Some(buffer) = self.diag_buffers.borrow_mut().last_mut() {
+            buffer.push(diag);
+            return;
+        }
+        self.sess.emit(diag);
+    }
 }
 
 impl DiagEmitter for GlobalContext<'_> {
@@ -220,7 +256,7 @@ impl DiagEmitter for GlobalContext<'_> {
         }
 
         // Emit the diagnostic.
-        self.sess.emit(diag);
+        self.forward_diag(diag);
 
         // If this is anything more than a warning, emit a backtrace in debug
         // builds.
@@ -372,6 +408,16 @@ pub struct GlobalTables<'t> {
     interned_param_envs: RefCell<HashMap<&'t ParamEnvData<'t>, ParamEnv>>,
     param_envs: RefCell<Vec<&'t ParamEnvData<'t>>>,
     param_env_contexts: RefCell<HashMap<ParamEnv, BTreeSet<NodeId>>>,
+    pub(crate) param_env_stats: RefCell<ParamEnvStats>,
+    pub(crate) param_env_instances: RefCell<HashMap<ParamEnv, BTreeSet<NodeId>>>,
+    /// The environment around the instantiation that first generated each
+    /// module instance environment.
+    pub(crate) param_env_parents: RefCell<HashMap<ParamEnv, ParamEnv>>,
+    pub(crate) param_override_targets: RefCell<HashMap<Span, NodeId>>,
+    pub(crate) param_overrides: RefCell<Vec<ParamOverride>>,
+    /// The number of positional parameter assignments seen per instantiation.
+    #[cfg(debug_assertions)]
+    pub(crate) positional_counts: RefCell<HashMap<NodeId, usize>>,
     node_id_to_parent_node_id: RefCell<HashMap<NodeId, NodeId>>,
     interned_values: RefCell<HashSet<Value<'t>>>,
     lowering_hints: RefCell<HashMap<NodeId, hir::Hint>>,
@@ -566,6 +612,17 @@ pub trait Context<'gcx>: DiagEmitter + QueryDatabase<'gcx> + ty::HasTypeStorage<
         if let Some(&x) = self.tables().interned_param_envs.borrow().get(&env) {
             return x;
         }
+        #[cfg(debug_assertions)]
+        {
+            if let Err(msg) = env.validate() {
+                panic!("invalid parameter environment {:?}: {}", env, msg);
+            }
+        }
+        {
+            let mut stats = self.tables().param_env_stats.borrow_mut();
+            stats.envs += 1;
+            stats.bindings += env.num_bindings();
+        }
         let data = self.arena().param_envs.alloc(env);
         let id = {
             let mut vec = self.tables().param_envs.borrow_mut();
@@ -580,17 +637,67 @@ pub trait Context<'gcx>: DiagEmitter + QueryDatabase<'gcx> + ty::HasTypeStorage<
         id
     }
 
+    /// Internalize a parameter environment constructed outside the context.
+    ///
+    /// This is the entry point for environments that have been built with
+    /// [`ParamEnvData::builder`] or rehydrated from a serialized form. All
+    /// environments the bindings refer to must already be interned in this
+    /// context. Note that serialized node ids are only meaningful within a
+    /// stable node numbering. Emits a diagnostic and fails if the environment
+    /// is malformed.
+    fn intern_param_env_from(&self, data: ParamEnvData<'gcx>) -> Result<ParamEnv> {
+        let count = self.tables().param_envs.borrow().len();
+        if let Some(env) = data
+            .referenced_envs()
+            .into_iter()
+            .find(|env| env.0 as usize >= count)
+        {
+            self.emit(DiagBuilder2::error(format!(
+                "parameter environment refers to {}, which has not been interned",
+                env
+            )));
+            return Err(());
+        }
+        #[cfg(debug_assertions)]
+        {
+            if let Err(msg) = data.validate() {
+                self.emit(DiagBuilder2::error(format!(
+                    "invalid parameter environment: {}",
+                    msg
+                )));
+                return Err(());
+            }
+        }
+        Ok(self.intern_param_env(data))
+    }
+
     /// Get the [`ParamEnvData`] associated with a [`ParamEnv`].
     fn param_env_data(&self, env: ParamEnv) -> &'gcx ParamEnvData<'gcx> {
         self.tables().param_envs.borrow()[env.0 as usize]
     }
 
+    /// Get the [`ParamEnvData`] associated with multiple [`ParamEnv`]s.
+    ///
+    /// This resolves all environments under a single borrow of the table.
+    fn param_env_datas(&self, envs: &[ParamEnv]) -> Vec<&'gcx ParamEnvData<'gcx>> {
+        let table = self.tables().param_envs.borrow();
+        envs.iter().map(|env| table[env.0 as usize]).collect()
+    }
+
+    /// Get statistics about the interned parameter environments.
+    ///
+    /// This is useful to diagnose elaboration blowups due to near-identical
+    /// environments that fail to deduplicate.
+    fn param_env_stats(&self) -> ParamEnvStats {
+        *self.tables().param_env_stats.borrow()
+    }
+
     /// Get the default parameter environment.
     ///
     /// This is useful for instantiations without any parameter assignment, e.g.
-    /// for the top-level module.
+    /// for the top-level module. It is the same as [`ParamEnv::EMPTY`].
     fn default_param_env(&self) -> ParamEnv {
-        self.intern_param_env(ParamEnvData::default())
+        ParamEnv::EMPTY
     }
 
     /// Associate a context with a param env.
@@ -619,6 +726,574 @@ pub trait Context<'gcx>: DiagEmitter + QueryDatabase<'gcx> + ty::HasTypeStorage<
             .unwrap_or_else(Default::default)
     }
 
+    /// Get the module instantiations that produced a parameter environment.
+    ///
+    /// Since environments are interned, all instantiations of a module with
+    /// the same parameter assignments share one environment. This lists the
+    /// instantiations whose environment has been computed so far, in the order
+    /// of their node ids.
+    fn instances_for_param_env(&self, env: ParamEnv) -> Vec<NodeId> {
+        self.tables()
+            .param_env_instances
+            .borrow()
+            .get(&env)
+            .map(|s| s.iter().cloned().collect())
+            .unwrap_or_else(Default::default)
+    }
+
+    /// Get the environment around the instantiation that produced a module
+    /// instance environment.
+    ///
+    /// This is the environment of the next level up in the hierarchy. If
+    /// several instantiations produced `env`, the first one computed is used.
+    fn param_env_parent(&self, env: ParamEnv) -> Option<ParamEnv> {
+        self.tables().param_env_parents.borrow().get(&env).cloned()
+    }
+
+    /// Find the parameter targeted by a named parameter override.
+    ///
+    /// `span` is the span of the parameter name in an override such as
+    /// `.WIDTH(8)`. This is intended for tooling, e.g. to jump to the
+    /// declaration of the parameter. Only overrides of instantiations whose
+    /// environment has been computed are known.
+    fn param_override_target(&self, span: Span) -> Option<NodeId> {
+        self.tables()
+            .param_override_targets
+            .borrow()
+            .get(&span)
+            .cloned()
+    }
+
+    /// Register an override of a parameter by its hierarchical path.
+    ///
+    /// The `path` consists of the name of the top-level module, followed by
+    /// the names of the instances leading to the module that declares the
+    /// parameter, followed by the name of the parameter itself, e.g.
+    /// `top.u_cpu.u_alu.WIDTH`. The expression `value` is evaluated in the
+    /// default parameter environment. Overrides are applied as the hierarchy
+    /// is elaborated, see [`apply_param_overrides`]. Emits an error and fails
+    /// if `path` does not name at least a module and a parameter.
+    ///
+    /// [`apply_param_overrides`]: Context::apply_param_overrides
+    fn register_param_override(&self, path: &[Name], value: NodeId) -> Result<()> {
+        if path.len() < 2 {
+            let path: Vec<_> = path.iter().map(|n| n.to_string()).collect();
+            self.emit(
+                DiagBuilder2::error(format!(
+                    "parameter override `{}` needs a module and a parameter name",
+                    path.join(".")
+                ))
+                .span(self.span(value)),
+            );
+            return Err(());
+        }
+        self.tables()
+            .param_overrides
+            .borrow_mut()
+            .push(ParamOverride {
+                path: path.to_vec(),
+                value,
+                matched: false,
+            });
+        Ok(())
+    }
+
+    /// Apply the registered parameter overrides to an instance.
+    ///
+    /// `path` is the hierarchical path of the instance, starting with the name
+    /// of the top-level module, and `env` the parameter environment computed
+    /// for it. Every override registered for a parameter of `module` at that
+    /// path replaces the corresponding binding in `env`.
+    ///
+    /// Note that all instances of a module with the same parameters are
+    /// elaborated only once. Overrides of parameters further down the
+    /// hierarchy should therefore only target instances that differ in their
+    /// parameters from all other instances of the same module.
+    fn apply_param_overrides(
+        &self,
+        module: NodeId,
+        path: &[Name],
+        mut env: ParamEnv,
+    ) -> Result<ParamEnv> {
+        let matching: Vec<_> = self
+            .tables()
+            .param_overrides
+            .borrow()
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| &o.path[..o.path.len() - 1] == path)
+            .map(|(index, o)| (index, *o.path.last().unwrap(), o.value))
+            .collect();
+        if matching.is_empty() {
+            return Ok(env);
+        }
+        let params = crate::param_env::params_of(self.gcx(), module)?;
+        for (index, name, value) in matching {
+            let target = params.iter().cloned().find(|&id| match self.ast_of(id) {
+                Ok(AstNode::TypeParam(_, p)) => p.name.value == name,
+                Ok(AstNode::ValueParam(_, p)) => p.name.value == name,
+                _ => false,
+            });
+            let target = match target {
+                Some(target) => target,
+                None => continue,
+            };
+            // Environments that are not associated with a module, like the one
+            // of the top-level module, need to know which module the override
+            // targets.
+            if self.param_env_data(env).module().is_none() {
+                let mut data = self.param_env_data(env).clone();
+                data.set_module(module);
+                env = self.intern_param_env(data);
+            }
+            env = self.gcx().param_env(ParamEnvSource::Defparam {
+                base: env,
+                env: self.default_param_env(),
+                target,
+                value,
+            })?;
+            self.tables().param_overrides.borrow_mut()[index].matched = true;
+        }
+        Ok(env)
+    }
+
+    /// Warn about the registered parameter overrides that did not match any
+    /// instance.
+    fn warn_unmatched_param_overrides(&self) {
+        for o in self.tables().param_overrides.borrow().iter() {
+            if !o.matched {
+                let path: Vec<_> = o.path.iter().map(|n| n.to_string()).collect();
+                self.emit(
+                    DiagBuilder2::warning(format!(
+                        "parameter override `{}` did not match any instance",
+                        path.join(".")
+                    ))
+                    .span(self.span(o.value)),
+                );
+            }
+        }
+    }
+
+    /// Run a closure with all emitted diagnostics buffered.
+    ///
+    /// The diagnostics emitted by `f` are only passed on if it returns `Ok`,
+    /// and are discarded otherwise. This allows for trial elaboration without
+    /// reporting the errors of the discarded attempts. Note that query results
+    /// are cached: a query that fails within `f` does not emit its diagnostics
+    /// again when it is called later on.
+    fn with_diag_buffer<R>(&self, f: impl FnOnce(&Self) -> Result<R>) -> Result<R>
+    where
+        Self: Sized,
+    {
+        let gcx = self.gcx();
+        gcx.diag_buffers.borrow_mut().push(vec![]);
+        let result = f(self);
+        let diags = gcx.diag_buffers.borrow_mut().pop().unwrap();
+        if result.is_ok() {
+            for diag in diags {
+                gcx.forward_diag(diag);
+            }
+        }
+        result
+    }
+
+    /// Get the maximum depth of nested instantiations.
+    ///
+    /// Elaboration gives up on instantiations nested deeper than this, which
+    /// usually indicates a module that instantiates itself.
+    fn inst_depth_limit(&self) -> usize {
+        self.gcx().inst_depth_limit.get()
+    }
+
+    /// Make sure an instantiation does not exceed the instantiation depth.
+    ///
+    /// `stack` lists the instantiations enclosing the one at `span`, outermost
+    /// first, and `env` is the environment of the instantiated module. Emits
+    /// an error that traces back through the innermost enclosing
+    /// instantiations if the depth limit has been reached.
+    fn check_inst_depth(&self, span: Span, stack: &[NodeId], env: ParamEnv) -> Result<()> {
+        if stack.len() < self.inst_depth_limit() {
+            return Ok(());
+        }
+        let mut d = DiagBuilder2::error(
+            "instantiation recursion limit exceeded, possible cyclic instantiation",
+        )
+        .span(span)
+        .add_note(format!(
+            "Instantiations are limited to a depth of {}",
+            self.inst_depth_limit()
+        ))
+        .add_note(format!(
+            "Innermost instantiation has parameters: {}",
+            self.param_env_to_string(env)
+        ));
+        for &id in stack.iter().rev().take(10) {
+            d = d
+                .add_note(format!("Through {} here:", self.ast_for_id(id)))
+                .span(self.span(id));
+        }
+        self.emit(d);
+        Err(())
+    }
+
+    /// Resolve the parameters of a module or interface in an environment.
+    ///
+    /// Returns the name of every parameter, in declaration order, together
+    /// with the value or type it evaluates to in `env`. This is intended for
+    /// elaboration reports and editor tooling.
+    fn module_params_resolved(
+        &self,
+        module: NodeId,
+        env: ParamEnv,
+    ) -> Result<Vec<(Name, ResolvedParamValue<'gcx>)>> {
+        let gcx = self.gcx();
+        let mut resolved = vec![];
+        for param_id in crate::param_env::params_of(gcx, module)? {
+            match self.ast_of(param_id)? {
+                AstNode::TypeParam(_, p) => {
+                    let ty = gcx.resolve_type_param(param_id, env)?;
+                    resolved.push((p.name.value, ResolvedParamValue::Type(ty)));
+                }
+                AstNode::ValueParam(_, p) => {
+                    let value = gcx.constant_value_of(param_id, env);
+                    if value.is_error() {
+                        return Err(());
+                    }
+                    resolved.push((p.name.value, ResolvedParamValue::Value(value)));
+                }
+                _ => unreachable!(),
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Find the AST node a parameter is bound to in an environment.
+    ///
+    /// Returns the expression or type assigned to `param` by an instantiation
+    /// or defparam, or the default it falls back to. Returns `None` if the
+    /// parameter is unbound, or bound directly to a value or type that has no
+    /// node in the source, such as the current iteration of a genvar. This is
+    /// intended for tools that highlight the source text of a binding.
+    fn param_binding_ast(&self, env: ParamEnv, param: NodeId) -> Result<Option<AstNode<'gcx>>> {
+        let data = self.param_env_data(env);
+        match data.get_value(param).or_else(|| data.get_type(param)) {
+            Some(id) => self.ast_of(id).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Find where the value or type of a parameter was assigned.
+    ///
+    /// Returns the span of the assignment in the instantiation or defparam
+    /// that bound `param` in `env`. Returns `None` if the parameter falls back
+    /// to its default, is unbound, or was bound without an assignment in the
+    /// source. Diagnostics about the value of a parameter use this to point
+    /// back at the override that supplied it.
+    fn param_assignment_span(&self, env: ParamEnv, param: NodeId) -> Option<Span> {
+        let data = self.param_env_data(env);
+        match (data.find_value(param), data.find_type(param)) {
+            (Some(ParamEnvBinding::Indirect(id)), _) | (_, Some(ParamEnvBinding::Indirect(id))) => {
+                Some(self.span(id.id()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Check whether two parameter environments are equivalent after folding.
+    ///
+    /// Two environments of the same module are equivalent if every value
+    /// parameter folds to the same constant, and every type parameter resolves
+    /// to the same type. This is weaker than comparing the handles, since the
+    /// handles differ as soon as the assignments differ textually, e.g. `#(8)`
+    /// and `#(4+4)`. Parameters bound in only one of the environments are
+    /// compared against their default in the other one. Fails if any of the
+    /// parameters does not evaluate, which has been reported already.
+    fn param_envs_equivalent(&self, a: ParamEnv, b: ParamEnv) -> Result<bool> {
+        if a == b {
+            return Ok(true);
+        }
+        let gcx = self.gcx();
+        let (data_a, data_b) = (self.param_env_data(a), self.param_env_data(b));
+        if let (Some(ma), Some(mb)) = (data_a.module(), data_b.module()) {
+            if ma != mb {
+                return Ok(false);
+            }
+        }
+        let values: BTreeSet<_> = data_a
+            .values()
+            .chain(data_b.values())
+            .map(|(id, _)| id)
+            .collect();
+        for id in values {
+            let value_a = gcx.constant_value_of(id, a);
+            let value_b = gcx.constant_value_of(id, b);
+            if value_a.is_error() || value_b.is_error() {
+                return Err(());
+            }
+            if value_a != value_b {
+                return Ok(false);
+            }
+        }
+        let types: BTreeSet<_> = data_a
+            .types()
+            .chain(data_b.types())
+            .map(|(id, _)| id)
+            .collect();
+        for id in types {
+            let ty_a = gcx.resolve_type_param(id, a)?;
+            let ty_b = gcx.resolve_type_param(id, b)?;
+            if !ty_a.is_strictly_identical(ty_b) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Execute the initialization step of a generate loop.
+    ///
+    /// Returns `env` extended with the initial value of the genvar.
+    fn execute_genvar_init(&self, id: NodeId, env: ParamEnv) -> Result<ParamEnv> {
+        let hir = self.gcx().hir_of(id)?;
+        match hir {
+            HirNode::GenvarDecl(_) => Ok(env),
+            HirNode::Stmt(stmt) => match stmt.kind {
+                hir::StmtKind::Assign {
+                    lhs,
+                    rhs,
+                    kind: hir::AssignKind::Block(ast::AssignOp::Identity),
+                } => {
+                    let target_id = self.gcx().resolve_node(lhs, env)?;
+                    self.gcx().param_env(ParamEnvSource::GenerateLoop {
+                        base: env,
+                        genvar: target_id,
+                        value: rhs,
+                    })
+                }
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    /// Execute the iteration step of a generate loop.
+    ///
+    /// Returns `env` with the genvar advanced to its next value.
+    fn execute_genvar_step(&self, id: NodeId, env: ParamEnv) -> Result<ParamEnv> {
+        let hir = self.gcx().hir_of(id)?;
+        let mut env_data = self.param_env_data(env).clone();
+        let next = match hir {
+            HirNode::Expr(expr) => match expr.kind {
+                hir::ExprKind::Unary(op, target_id) => {
+                    let target_id = self.gcx().resolve_node(target_id, env)?;
+                    let current_value = self.gcx().constant_value_of(target_id, env);
+                    let next_value = match current_value.kind {
+                        ValueKind::Int(ref v, ..) => match op {
+                            hir::UnaryOp::PostInc | hir::UnaryOp::PreInc => Some(v + 1),
+                            hir::UnaryOp::PostDec | hir::UnaryOp::PreDec => Some(v - 1),
+                            _ => None,
+                        }
+                        .map(|v| value::make_int(current_value.ty, v)),
+                        _ => unreachable!(),
+                    };
+                    next_value.map(|v| (target_id, self.intern_value(v)))
+                }
+                hir::ExprKind::Assign { .. } => {
+                    let mir = self.gcx().mir_rvalue(id, env);
+                    match mir.kind {
+                        mir::RvalueKind::Error => return Err(()),
+                        mir::RvalueKind::Assignment { lvalue, rvalue, .. } => {
+                            let target_id = match lvalue.kind {
+                                mir::LvalueKind::Error => return Err(()),
+                                mir::LvalueKind::Genvar(id) => id,
+                                _ => unreachable!(),
+                            };
+                            let next_value = self.gcx().const_mir_rvalue(Ref(rvalue));
+                            Some((target_id, next_value))
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        match next {
+            Some((target_id, next_value)) => {
+                env_data.set_value(target_id, next_value);
+                return Ok(self.intern_param_env(env_data));
+            }
+            None => {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "{} is not a valid genvar iteration step",
+                        hir.desc_full()
+                    ))
+                    .span(hir.human_span()),
+                );
+                Err(())
+            }
+        }
+    }
+
+    /// Evaluate the condition of an if-generate statement.
+    ///
+    /// Folds the condition expression `cond` in `env`, such that only the
+    /// selected branch is elaborated. Fails if the condition is not a constant,
+    /// in which case neither branch should be elaborated.
+    fn eval_generate_condition(&self, cond: NodeId, env: ParamEnv) -> Result<bool> {
+        let value = self.gcx().constant_value_of(cond, env);
+        if value.is_error() {
+            return Err(());
+        }
+        Ok(value.is_true())
+    }
+
+    /// Find the modules that are not instantiated anywhere in the design.
+    ///
+    /// These are the candidates for the top-level module passed to
+    /// [`elaborate_from_top`]. Any textual instantiation counts, including
+    /// ones in generate branches that end up not being selected, but a module
+    /// instantiating itself does not. The modules are returned in the order
+    /// in which they appear in the source.
+    ///
+    /// [`elaborate_from_top`]: Context::elaborate_from_top
+    fn top_modules(&self) -> Vec<NodeId> {
+        let gcx = self.gcx();
+        let mut visitor = InstTargetCollector {
+            current: None,
+            targets: HashSet::new(),
+        };
+        for root in gcx.roots() {
+            root.accept(&mut visitor);
+        }
+        let mut tops: Vec<_> = gcx
+            .modules()
+            .filter(|(name, _)| !visitor.targets.contains(name))
+            .map(|(_, id)| id)
+            .collect();
+        tops.sort();
+        tops
+    }
+
+    /// Elaborate the design hierarchy below a top-level module.
+    ///
+    /// Returns every module and interface instance in the hierarchy, in
+    /// depth-first order, together with the parameter environment it is
+    /// elaborated in. The list starts with `top` itself, in the environment
+    /// produced by the parameter overrides of the session. Generate blocks
+    /// are resolved the same way as during code generation, and the same
+    /// instantiation depth limit applies. Elaboration carries on past errors
+    /// in an instance, such that the diagnostics of the entire design are
+    /// reported at once.
+    fn elaborate_from_top(&self, top: NodeId) -> Result<Vec<(NodeId, ParamEnv)>>
+    where
+        Self: Sized,
+    {
+        let module = match self.gcx().hir_of(top)? {
+            HirNode::Module(m) => m,
+            _ => {
+                self.emit(
+                    DiagBuilder2::error(format!("{} is not a module", self.ast_for_id(top)))
+                        .span(self.span(top))
+                        .add_note("Only modules can be elaborated as the top of a hierarchy"),
+                );
+                return Err(());
+            }
+        };
+        self.gcx().check_module_params(top)?;
+        let env =
+            self.apply_param_overrides(top, &[module.name.value], self.default_param_env())?;
+        let mut elab = Elaboration {
+            insts: vec![(top, env)],
+            stack: vec![],
+            path: vec![module.name.value],
+            failed: false,
+            aborted: false,
+        };
+        elaborate_block(self, &module.block, env, &mut elab);
+        self.warn_unmatched_param_overrides();
+        if elab.failed {
+            Err(())
+        } else {
+            Ok(elab.insts)
+        }
+    }
+
+    /// Render the parameters of a parameter environment.
+    ///
+    /// Produces a list such as `W=8, DEPTH=16, T=logic [3:0]`, where value
+    /// parameters show their folded value and type parameters their resolved
+    /// type. If the environment is not associated with a module, or its
+    /// parameters cannot be resolved, the bindings are listed as they are
+    /// recorded in the environment instead, with defaults marked as such.
+    fn param_env_to_string(&self, env: ParamEnv) -> String {
+        let data = self.param_env_data(env);
+        let resolved = data
+            .module()
+            .ok_or(())
+            .and_then(|module| self.module_params_resolved(module, env));
+        if let Ok(params) = resolved {
+            return params
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>()
+                .join(", ");
+        }
+        let name_of = |id: NodeId| match self.ast_of(id) {
+            Ok(AstNode::TypeParam(_, p)) => p.name.value.to_string(),
+            Ok(AstNode::ValueParam(_, p)) => p.name.value.to_string(),
+            _ => format!("{:?}", id),
+        };
+        let mut bindings: Vec<_> = data
+            .types()
+            .map(|(id, binding)| {
+                let bound = match binding {
+                    ParamEnvBinding::Direct(ty) => ty.to_string(),
+                    ParamEnvBinding::Indirect(id) => self.span(id.id()).extract(),
+                    ParamEnvBinding::Default(id) => {
+                        format!("{} (default)", self.span(id).extract())
+                    }
+                };
+                (id, format!("{}={}", name_of(id), bound))
+            })
+            .chain(data.values().map(|(id, binding)| {
+                let bound = match binding {
+                    ParamEnvBinding::Direct(value) => value.to_string(),
+                    ParamEnvBinding::Indirect(id) => self.span(id.id()).extract(),
+                    ParamEnvBinding::Default(id) => {
+                        format!("{} (default)", self.span(id).extract())
+                    }
+                };
+                (id, format!("{}={}", name_of(id), bound))
+            }))
+            .collect();
+        bindings.sort_by_key(|&(id, _)| id);
+        bindings
+            .into_iter()
+            .map(|(_, b)| b)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Describe a parameter environment.
+    ///
+    /// This renders the environment together with the module it belongs to
+    /// and its parameters as produced by [`param_env_to_string`], and is
+    /// intended as a debugging aid.
+    ///
+    /// [`param_env_to_string`]: Context::param_env_to_string
+    fn describe_param_env(&self, env: ParamEnv) -> String {
+        match self.param_env_data(env).module() {
+            Some(id) => format!(
+                "{} of {}: {}",
+                env,
+                self.ast_for_id(id),
+                self.param_env_to_string(env)
+            ),
+            None => format!("{}: {}", env, self.param_env_to_string(env)),
+        }
+    }
+
     /// Associate a parent with a node.
     ///
     /// Panics if `node_id` already has a parent assigned.
@@ -702,6 +1377,17 @@ pub trait Context<'gcx>: DiagEmitter + QueryDatabase<'gcx> + ty::HasTypeStorage<
         match self.gcx().constant_value_of(node_id, env).kind {
             ValueKind::Int(ref x, ..) => Ok(x),
             ValueKind::Error => Err(()),
+            ValueKind::Unbounded => {
+                let hir = self.gcx().hir_of(node_id)?;
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "{} is unbounded and has no integer value",
+                        hir.desc_full()
+                    ))
+                    .span(hir.human_span()),
+                );
+                Err(())
+            }
             _ => {
                 let hir = self.gcx().hir_of(node_id)?;
                 self.emit(
@@ -714,6 +1400,163 @@ pub trait Context<'gcx>: DiagEmitter + QueryDatabase<'gcx> + ty::HasTypeStorage<
     }
 }
 
+/// The state of an ongoing [`Context::elaborate_from_top`].
+struct Elaboration {
+    /// The instances elaborated so far.
+    insts: Vec<(NodeId, ParamEnv)>,
+    /// The instantiations currently being elaborated.
+    stack: Vec<NodeId>,
+    /// The instance names leading to the current instance.
+    path: Vec<Name>,
+    /// Whether elaborating any instance failed.
+    failed: bool,
+    /// Whether the instantiation depth limit was exceeded, in which case the
+    /// remaining instances are skipped.
+    aborted: bool,
+}
+
+/// Elaborate the instances and generate blocks of a module.
+fn elaborate_block<'gcx>(
+    cx: &impl Context<'gcx>,
+    block: &'gcx hir::ModuleBlock,
+    env: ParamEnv,
+    elab: &mut Elaboration,
+) {
+    for &inst_id in &block.insts {
+        if elab.aborted {
+            return;
+        }
+        if elaborate_inst(cx, inst_id, env, elab).is_err() {
+            elab.failed = true;
+        }
+    }
+    for &gen_id in &block.gens {
+        if elab.aborted {
+            return;
+        }
+        if elaborate_gen(cx, gen_id, env, elab).is_err() {
+            elab.failed = true;
+        }
+    }
+}
+
+/// Elaborate an instantiation and the module it instantiates.
+fn elaborate_inst<'gcx>(
+    cx: &impl Context<'gcx>,
+    inst_id: NodeId,
+    env: ParamEnv,
+    elab: &mut Elaboration,
+) -> Result<()> {
+    let inst = match cx.gcx().hir_of(inst_id)? {
+        HirNode::Inst(x) => x,
+        _ => unreachable!(),
+    };
+    let inst = cx.gcx().inst_details(Ref(inst), env)?;
+    let target_module = match inst.target.kind {
+        InstTarget::Module(x) => cx.gcx().hir_of_module(x)?,
+        InstTarget::Interface(_) => {
+            elab.insts.push((inst_id, inst.inner_env));
+            return Ok(());
+        }
+    };
+
+    // Make sure we do not recur indefinitely through a cyclic instantiation.
+    if cx
+        .check_inst_depth(inst.hir.ast.name.span, &elab.stack, inst.inner_env)
+        .is_err()
+    {
+        elab.aborted = true;
+        return Err(());
+    }
+
+    elab.path.push(inst.hir.ast.name.value);
+    let target_env = cx.apply_param_overrides(target_module.id, &elab.path, inst.inner_env);
+    if let Ok(target_env) = target_env {
+        elab.insts.push((inst_id, target_env));
+        elab.stack.push(inst_id);
+        elaborate_block(cx, &target_module.block, target_env, elab);
+        elab.stack.pop();
+    }
+    elab.path.pop();
+    target_env.map(|_| ())
+}
+
+/// Elaborate the selected branch or the iterations of a generate block.
+fn elaborate_gen<'gcx>(
+    cx: &impl Context<'gcx>,
+    gen_id: NodeId,
+    env: ParamEnv,
+    elab: &mut Elaboration,
+) -> Result<()> {
+    let hir = match cx.gcx().hir_of(gen_id)? {
+        HirNode::Gen(x) => x,
+        _ => unreachable!(),
+    };
+    match hir.kind {
+        hir::GenKind::If {
+            cond,
+            ref main_body,
+            ref else_body,
+        } => {
+            if cx.eval_generate_condition(cond, env)? {
+                elaborate_block(cx, main_body, env, elab);
+            } else if let Some(else_body) = else_body {
+                elaborate_block(cx, else_body, env, elab);
+            }
+        }
+        hir::GenKind::For {
+            ref init,
+            cond,
+            step,
+            ref body,
+        } => {
+            let mut local_env = env;
+            for &i in init {
+                local_env = cx.execute_genvar_init(i, local_env)?;
+            }
+            while !elab.aborted && cx.gcx().constant_value_of(cond, local_env).is_true() {
+                elaborate_block(cx, body, local_env, elab);
+                local_env = cx.execute_genvar_step(step, local_env)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A visitor that collects the names of instantiated modules and interfaces.
+///
+/// See [`Context::top_modules`].
+struct InstTargetCollector {
+    /// The name of the design unit being visited.
+    current: Option<Name>,
+    /// The names instantiated outside of the module they name.
+    targets: HashSet<Name>,
+}
+
+impl<'a> ast::Visitor<'a> for InstTargetCollector {
+    fn pre_visit_module(&mut self, node: &'a ast::Module<'a>) -> bool {
+        self.current = Some(node.name.value);
+        true
+    }
+
+    fn pre_visit_interface(&mut self, node: &'a ast::Interface<'a>) -> bool {
+        self.current = Some(node.name.value);
+        true
+    }
+
+    fn pre_visit_package(&mut self, node: &'a ast::Package<'a>) -> bool {
+        self.current = Some(node.name.value);
+        true
+    }
+
+    fn pre_visit_inst(&mut self, node: &'a ast::Inst<'a>) -> bool {
+        if self.current != Some(node.target.value) {
+            self.targets.insert(node.target.value);
+        }
+        true
+    }
+}
+
 /// An ugly hack to get the new AST nodes to hook into the ID-based AST lookup
 /// during the transition phase.
 struct AstMapRegistrator<'a, 'b> {
@@ -35,30 +35,19 @@ pub(crate) fn hir_of<'a>(cx: &impl Context<'a>, node_id: NodeId) -> Result<HirNo
         },
         AstNode::Expr(expr) => cx.hir_of_expr(Ref(expr)).map(HirNode::Expr),
         AstNode::InstTarget(ast) => {
+            // Positional parameters after named ones are rejected when the
+            // parameter environment is computed.
             let mut named_params = vec![];
             let mut pos_params = vec![];
-            let mut is_pos = true;
             for param in &ast.params {
-                let value_id = cx.map_ast_with_parent(AstNode::TypeOrExpr(&param.expr), node_id);
+                let value_id = param
+                    .expr
+                    .as_ref()
+                    .map(|expr| cx.map_ast_with_parent(AstNode::TypeOrExpr(expr), node_id));
                 if let Some(name) = param.name {
-                    is_pos = false;
-                    named_params.push((
-                        param.span,
-                        Spanned::new(name.name, name.span),
-                        Some(value_id),
-                    ));
+                    named_params.push((param.span, Spanned::new(name.name, name.span), value_id));
                 } else {
-                    if !is_pos {
-                        cx.emit(
-                            DiagBuilder2::warning("positional parameters must appear before named")
-                                .span(param.span)
-                                .add_note(format!(
-                                    "assuming this refers to argument #{}",
-                                    pos_params.len() + 1
-                                )),
-                        );
-                    }
-                    pos_params.push((param.span, Some(value_id)));
+                    pos_params.push((param.span, value_id));
                 }
             }
             let hir = hir::InstTarget {
@@ -413,6 +402,7 @@ fn lower_module_block<'gcx>(
         gens: Vec::new(),
         params: Vec::new(),
         assigns: Vec::new(),
+        defparams: Vec::new(),
         last_rib: parent_rib,
     };
     lower_module_block_into(cx, items, allow_ports, allow_modports, &mut block)?;
@@ -512,6 +502,13 @@ fn lower_module_block_into<'gcx>(
                     into.assigns.push(id);
                 }
             }
+            ast::ItemData::Defparam(ref defparam) => {
+                for &(ref lhs, ref rhs) in &defparam.assignments {
+                    let lhs_id = cx.map_ast_with_parent(AstNode::Expr(lhs), into.last_rib);
+                    let rhs_id = cx.map_ast_with_parent(AstNode::Expr(rhs), into.last_rib);
+                    into.defparams.push((lhs_id, rhs_id));
+                }
+            }
             ast::ItemData::ImportDecl(ref decl) => {
                 for item in &decl.items {
                     let id = cx.map_ast_with_parent(AstNode::Import(item), into.last_rib);
@@ -865,6 +862,7 @@ fn lower_expr_inner<'gcx>(
         }
 
         ast::IdentExpr(ident) => hir::ExprKind::Ident(ident),
+        ast::DollarExpr => hir::ExprKind::Unbounded,
         ast::UnaryExpr {
             op,
             expr: ref arg,
@@ -1677,6 +1675,7 @@ fn lower_call<'a>(
                 "onehot" => hir::BuiltinCall::OneHot(map_unary()?),
                 "onehot0" => hir::BuiltinCall::OneHot0(map_unary()?),
                 "isunknown" => hir::BuiltinCall::IsUnknown(map_unary()?),
+                "isunbounded" => hir::BuiltinCall::IsUnbounded(map_unary_id()?),
                 "left" => map_array_dim(hir::ArrayDim::Left)?,
                 "right" => map_array_dim(hir::ArrayDim::Right)?,
                 "low" => map_array_dim(hir::ArrayDim::Low)?,
@@ -207,6 +207,9 @@ pub struct ModuleBlock {
     pub params: Vec<NodeId>,
     /// The continuous assignments in the module.
     pub assigns: Vec<NodeId>,
+    /// The parameter overrides in the module, as pairs of the overridden
+    /// hierarchical name and the assigned value.
+    pub defparams: Vec<(NodeId, NodeId)>,
     /// The bottom of the name scope tree.
     pub last_rib: NodeId,
 }
@@ -646,6 +649,8 @@ pub enum ExprKind<'a> {
     TimeConst(BigRational),
     /// A string constant literal.
     StringConst(Spanned<Name>),
+    /// The unbounded marker `$`.
+    Unbounded,
     /// An identifier.
     Ident(Spanned<Name>),
     /// A unary operator.
@@ -858,6 +863,8 @@ pub enum BuiltinCall<'a> {
     OneHot0(&'a ast::Expr<'a>),
     /// A call to the `$isunknown(x)` function.
     IsUnknown(&'a ast::Expr<'a>),
+    /// A call to the `$isunbounded(x)` function.
+    IsUnbounded(NodeId),
     /// A call to one of the array dimension functions.
     ArrayDim(ArrayDim, &'a ast::Expr<'a>, Option<&'a ast::Expr<'a>>),
 }
@@ -224,7 +224,8 @@ pub fn walk_expr<'a>(visitor: &mut impl Visitor<'a>, expr: &'a Expr, lvalue: boo
         | ExprKind::IntConst { .. }
         | ExprKind::UnsizedConst(_)
         | ExprKind::TimeConst(_)
-        | ExprKind::StringConst(_) => (),
+        | ExprKind::StringConst(_)
+        | ExprKind::Unbounded => (),
         ExprKind::Ident(x) => {
             visitor.visit_ident(x);
         }
@@ -252,7 +253,8 @@ pub fn walk_expr<'a>(visitor: &mut impl Visitor<'a>, expr: &'a Expr, lvalue: boo
         }
         ExprKind::Builtin(BuiltinCall::Clog2(arg))
         | ExprKind::Builtin(BuiltinCall::Signed(arg))
-        | ExprKind::Builtin(BuiltinCall::Unsigned(arg)) => {
+        | ExprKind::Builtin(BuiltinCall::Unsigned(arg))
+        | ExprKind::Builtin(BuiltinCall::IsUnbounded(arg)) => {
             visitor.visit_node_with_id(arg, false);
         }
         ExprKind::Builtin(BuiltinCall::CountOnes(arg))
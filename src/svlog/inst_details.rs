@@ -4,6 +4,7 @@
 
 #[warn(missing_docs)]
 use crate::{
+    ast_map::AstNode,
     crate_prelude::*,
     hir::{self, HirNode},
     port_list::AsPortedNode,
@@ -67,13 +68,18 @@ pub(crate) fn inst_details<'a>(
     // Determine the details of the instantiation target.
     let target = cx.inst_target_details(Ref(inst_target), env)?;
 
+    // Apply the parameter overrides of the surrounding module to this
+    // particular instance.
+    let defparam_env = apply_defparams(cx, inst, target.inner_env, env)?;
+    cx.check_param_assignments(defparam_env, Some(inst_target.ast.id()))?;
+
     // Determine the port connections of the instantiations. Connections
     // are made to the module's external ports, and must later be mapped
     // to the actual internal ports in a second step.
     let port_mapping = cx.port_mapping(
         target.kind.as_any().as_all().get_ported().unwrap(),
         target.outer_env,
-        target.inner_env,
+        defparam_env,
         Ref(inst),
         &inst.pos_ports,
         &inst.named_ports,
@@ -98,7 +104,7 @@ pub(crate) fn inst_details<'a>(
         };
 
         // Check if the port is actually an interface.
-        let ty = cx.packed_type_from_ast(Ref(data.ty), target.inner_env, None);
+        let ty = cx.packed_type_from_ast(Ref(data.ty), defparam_env, None);
         if ty.get_interface().is_none() {
             continue;
         }
@@ -116,7 +122,7 @@ pub(crate) fn inst_details<'a>(
     // If we have found any additional parametrization, create an extended
     // parameter environment for this instance.
     let inner_env = if !intf_params.is_empty() {
-        let mut params = target.params.clone();
+        let mut params = cx.param_env_data(defparam_env).clone();
         params.add_interfaces(intf_params);
         trace!(
             "Extended parametrization with implicit interface parameters: {:?}",
@@ -124,7 +130,7 @@ pub(crate) fn inst_details<'a>(
         );
         cx.intern_param_env(params)
     } else {
-        target.inner_env
+        defparam_env
     };
 
     // Wrap everything up.
@@ -136,6 +142,148 @@ pub(crate) fn inst_details<'a>(
     }))
 }
 
+/// Compute the parameter environments of the elements of an instance array.
+///
+/// Returns one environment per element of the array `foo u[3:0]()`, ordered
+/// from the left to the right bound, together with the element's index. All
+/// elements share one environment, see `ParamEnvSource::ModuleInstArray`. `env`
+/// is the environment around the instantiation. Only one-dimensional arrays of
+/// modules are supported.
+pub(crate) fn inst_array_envs<'a>(
+    cx: &impl Context<'a>,
+    inst: &InstDetails<'a>,
+    env: ParamEnv,
+) -> Result<Vec<(num::BigInt, ParamEnv)>> {
+    let module = match inst.target.kind {
+        InstTarget::Module(x) => cx.hir_of_module(x)?,
+        _ => unreachable!(),
+    };
+    let (left, right) = match inst.hir.ast.dims.as_slice() {
+        [ast::TypeDim::Range(lhs, rhs)] => (
+            cx.constant_int_value_of(lhs.id(), env)?.clone(),
+            cx.constant_int_value_of(rhs.id(), env)?.clone(),
+        ),
+        [ast::TypeDim::Expr(size)] => {
+            let size = cx.constant_int_value_of(size.id(), env)?;
+            (num::zero(), size - 1)
+        }
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "unsupported: dimensions of instance array `{}`",
+                    inst.hir.name
+                ))
+                .span(inst.hir.ast.span())
+                .add_note("Only arrays with a single `[a:b]` or `[n]` dimension are supported"),
+            );
+            return Err(());
+        }
+    };
+    let element_env = cx.param_env(ParamEnvSource::ModuleInstArray {
+        module: Ref(module),
+        inst: inst.target.hir.ast.id(),
+        env: inst.target.outer_env,
+        pos: &inst.target.hir.pos_params,
+        named: &inst.target.hir.named_params,
+        parent_env: cx.param_env_parent(inst.target.outer_env),
+    })?;
+    let element_env = apply_defparams(cx, inst.hir, element_env, env)?;
+    let step = if left <= right {
+        num::BigInt::from(1)
+    } else {
+        num::BigInt::from(-1)
+    };
+    let mut envs = vec![];
+    let mut index = left;
+    loop {
+        envs.push((index.clone(), element_env));
+        if index == right {
+            break;
+        }
+        index += &step;
+    }
+    Ok(envs)
+}
+
+/// Apply the `defparam` statements of the surrounding module to an instance.
+///
+/// Only overrides of the form `inst.PARAM` that appear directly in the body of
+/// the module containing the instance are considered. `inner_env` is the
+/// environment generated by the instantiation, and `env` the environment in
+/// which the overriding values are evaluated.
+fn apply_defparams<'a>(
+    cx: &impl Context<'a>,
+    inst: &'a hir::Inst<'a>,
+    mut inner_env: ParamEnv,
+    env: ParamEnv,
+) -> Result<ParamEnv> {
+    // Find the module that contains the instance.
+    let mut rib = inst.target;
+    let parent = loop {
+        rib = match cx.parent_node_id(rib) {
+            Some(id) => id,
+            None => return Ok(inner_env),
+        };
+        match cx.ast_of(rib)? {
+            AstNode::Module(ast) => break cx.hir_of_module(ast)?,
+            AstNode::GenIf(..) | AstNode::GenFor(..) | AstNode::GenCase(..) => {
+                return Ok(inner_env)
+            }
+            _ => (),
+        }
+    };
+    if parent.block.defparams.is_empty() {
+        return Ok(inner_env);
+    }
+
+    // Override the parameters assigned to this instance.
+    for &(lhs_id, value) in &parent.block.defparams {
+        let lhs = match cx.ast_of(lhs_id)? {
+            AstNode::Expr(x) => x,
+            _ => unreachable!(),
+        };
+        let name = match lhs.data {
+            ast::MemberExpr { ref expr, name } => match expr.data {
+                ast::IdentExpr(n) if n.value == inst.name.value => name,
+                _ => continue,
+            },
+            _ => continue,
+        };
+        let module = match cx.param_env_data(inner_env).module() {
+            Some(module) => module,
+            None => break,
+        };
+        let target = crate::param_env::params_of(cx, module)?
+            .into_iter()
+            .find(|&id| match cx.ast_of(id) {
+                Ok(AstNode::TypeParam(_, p)) => p.name.value == name.value,
+                Ok(AstNode::ValueParam(_, p)) => p.name.value == name.value,
+                _ => false,
+            });
+        let target = match target {
+            Some(target) => target,
+            None => {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "{} has no parameter `{}`",
+                        cx.ast_for_id(module),
+                        name.value
+                    ))
+                    .span(name.span),
+                );
+                return Err(());
+            }
+        };
+        inner_env = cx.param_env(ParamEnvSource::Defparam {
+            base: inner_env,
+            env,
+            target,
+            value,
+        })?;
+    }
+    Ok(inner_env)
+}
+
 /// Compute the details of an instantiated module or interface.
 #[moore_derive::query]
 pub(crate) fn inst_target_details<'a>(
@@ -148,20 +296,7 @@ pub(crate) fn inst_target_details<'a>(
 
     // Create a new parameter environment that is generated by the
     // parametrization of this instance.
-    let inst_env = cx.param_env(match target {
-        resolver::InstTarget::Module(node) => ParamEnvSource::ModuleInst {
-            module: Ref(cx.hir_of_module(node)?),
-            env,
-            pos: &inst_target.pos_params,
-            named: &inst_target.named_params,
-        },
-        resolver::InstTarget::Interface(node) => ParamEnvSource::InterfaceInst {
-            interface: Ref(cx.hir_of_interface(node)?),
-            env,
-            pos: &inst_target.pos_params,
-            named: &inst_target.named_params,
-        },
-    })?;
+    let inst_env = cx.param_env(ParamEnvSource::from_inst(cx, inst_target.ast.id(), env)?)?;
     let inst_env_data = cx.param_env_data(inst_env);
 
     // Wrap everything up.
@@ -174,6 +309,57 @@ pub(crate) fn inst_target_details<'a>(
     }))
 }
 
+/// Collect the instantiations in the body of a module or interface.
+///
+/// Generate-if blocks are only descended into if their condition holds in
+/// `env`. The bodies of generate loops are always included, since the value of
+/// the loop variables is only known once the loop is unrolled.
+#[moore_derive::query]
+pub(crate) fn instances_of<'a>(
+    cx: &impl Context<'a>,
+    node_id: NodeId,
+    env: ParamEnv,
+) -> Result<Vec<NodeId>> {
+    let block = match cx.hir_of(node_id)? {
+        HirNode::Module(x) => &x.block,
+        HirNode::Interface(x) => &x.block,
+        _ => unreachable!(),
+    };
+    let mut insts = vec![];
+    collect_instances(cx, block, env, &mut insts)?;
+    Ok(insts)
+}
+
+fn collect_instances<'a>(
+    cx: &impl Context<'a>,
+    block: &'a hir::ModuleBlock,
+    env: ParamEnv,
+    into: &mut Vec<NodeId>,
+) -> Result<()> {
+    into.extend(block.insts.iter().cloned());
+    for &gen_id in &block.gens {
+        let hir = match cx.hir_of(gen_id)? {
+            HirNode::Gen(x) => x,
+            _ => unreachable!(),
+        };
+        match hir.kind {
+            hir::GenKind::If {
+                cond,
+                ref main_body,
+                ref else_body,
+            } => {
+                if cx.eval_generate_condition(cond, env)? {
+                    collect_instances(cx, main_body, env, into)?;
+                } else if let Some(else_body) = else_body {
+                    collect_instances(cx, else_body, env, into)?;
+                }
+            }
+            hir::GenKind::For { ref body, .. } => collect_instances(cx, body, env, into)?,
+        }
+    }
+    Ok(())
+}
+
 /// A visitor that emits instantiation details diagnostics.
 pub struct InstVerbosityVisitor<'a, 'gcx> {
     cx: &'a GlobalContext<'gcx>,
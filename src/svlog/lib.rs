@@ -141,7 +141,9 @@ pub use crate::{
     context::*,
     inst_details::{InstDetails, InstTargetDetails, InstVerbosityVisitor},
     param_env::{
-        IntoNodeEnvId, NodeEnvId, ParamEnv, ParamEnvBinding, ParamEnvData, ParamEnvSource,
+        param_env_checked, param_env_diff, DefaultParamEnvMangler, IntoNodeEnvId, NodeEnvId,
+        ParamEnv, ParamEnvBinding, ParamEnvData, ParamEnvDataBuilder, ParamEnvDiff, ParamEnvError,
+        ParamEnvMangler, ParamEnvSource, ParamEnvStats, ResolvedParamValue,
     },
     port_mapping::{PortMapping, PortMappingSource},
     // resolver::*,
@@ -161,6 +161,7 @@ fn lower_expr_inner<'gcx>(
             bug_span!(span, cx, "unsized const with weird '{}' char", c)
         }
         hir::ExprKind::TimeConst(ref k) => Ok(builder.constant(value::make_time(k.clone()))),
+        hir::ExprKind::Unbounded => Ok(builder.constant(value::make_unbounded(ty))),
         hir::ExprKind::StringConst(string) => Ok(builder.constant(value::make_int(
             // TODO: This could use `value::make_string` to build a string
             // value, and then resort to the conversion function there to map
@@ -230,6 +231,14 @@ fn lower_expr_inner<'gcx>(
             // Since we currently don't emit logic types, this is always zero.
             Ok(builder.constant(value::make_int(ty, num::zero())))
         }
+        hir::ExprKind::Builtin(hir::BuiltinCall::IsUnbounded(arg)) => {
+            let arg_val = cx.constant_value_of(arg, env);
+            if arg_val.is_error() {
+                return Ok(builder.error());
+            }
+            let value = arg_val.is_unbounded() as usize;
+            Ok(builder.constant(value::make_int(ty, value.into())))
+        }
         hir::ExprKind::Builtin(hir::BuiltinCall::ArrayDim(func, arg, dim)) => {
             // Decide which dimension to inspect.
             let dim = match dim {
@@ -3,12 +3,18 @@
 //! A parameter environment generated by an instantiation.
 
 use crate::{
+    ast::AcceptVisitor,
     ast_map::AstNode,
     crate_prelude::*,
-    hir::{NamedParam, PosParam},
+    hir::{HirNode, NamedParam, PosParam},
+    resolver::InstTarget,
     ty::UnpackedType,
     value::Value,
 };
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::Arc,
+};
 
 /// A parameter environment.
 ///
@@ -17,6 +23,36 @@ use crate::{
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ParamEnv(pub(crate) u32);
 
+impl ParamEnv {
+    /// The empty parameter environment.
+    ///
+    /// This environment has no bindings and is not associated with any module.
+    /// It is interned when the context is created, and is used to elaborate
+    /// the top-level modules of a design.
+    pub const EMPTY: ParamEnv = ParamEnv(0);
+
+    /// Complete a partial parameter environment.
+    ///
+    /// Binds every open parameter listed in `more` to the node assigned to it,
+    /// which is evaluated in `env`. The remaining open parameters fall back to
+    /// their default. Completing an environment that has no open parameters
+    /// yields an equivalent environment.
+    pub fn complete<'a>(
+        self,
+        cx: &impl Context<'a>,
+        env: ParamEnv,
+        more: &'a [(NodeId, NodeId)],
+    ) -> Result<ParamEnv> {
+        let env = cx.param_env(ParamEnvSource::Complete {
+            base: self,
+            env,
+            more,
+        })?;
+        cx.check_param_assignments(env, None)?;
+        Ok(env)
+    }
+}
+
 impl std::fmt::Display for ParamEnv {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "p{}", self.0)
@@ -75,15 +111,50 @@ impl std::fmt::Debug for NodeEnvId {
 }
 
 /// A parameter environment.
-#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+///
+/// Two environments are considered equal if they have the same bindings and
+/// leave the same parameters open. The span of the code that produced the
+/// environment and which parameters fell back to their default are carried
+/// along as metadata, but are not considered in comparisons and hashing.
+#[derive(Debug, Default, Clone)]
 pub struct ParamEnvData<'t> {
     module: Option<NodeId>,
     values: Vec<(NodeId, ParamEnvBinding<Value<'t>>)>,
     types: Vec<(NodeId, ParamEnvBinding<&'t UnpackedType<'t>>)>,
     intfs: Vec<(NodeId, NodeEnvId)>,
+    span: Option<Span>,
+    defaulted: Vec<NodeId>,
+    open: Vec<NodeId>,
+}
+
+impl PartialEq for ParamEnvData<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.module == other.module
+            && self.values == other.values
+            && self.types == other.types
+            && self.intfs == other.intfs
+            && self.open == other.open
+    }
+}
+
+impl Eq for ParamEnvData<'_> {}
+
+impl std::hash::Hash for ParamEnvData<'_> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.module.hash(state);
+        self.values.hash(state);
+        self.types.hash(state);
+        self.intfs.hash(state);
+        self.open.hash(state);
+    }
 }
 
 impl<'t> ParamEnvData<'t> {
+    /// Create a builder for a new parameter environment.
+    pub fn builder() -> ParamEnvDataBuilder<'t> {
+        ParamEnvDataBuilder::default()
+    }
+
     /// Find the value assigned to a node.
     pub fn find_value(&self, node_id: NodeId) -> Option<ParamEnvBinding<Value<'t>>> {
         self.values
@@ -100,6 +171,105 @@ impl<'t> ParamEnvData<'t> {
             .map(|&(_, id)| id)
     }
 
+    /// Find the node assigned to a value parameter.
+    ///
+    /// Returns `None` if the parameter is unbound or bound directly to a value.
+    pub fn get_value(&self, param_id: NodeId) -> Option<NodeId> {
+        self.find_value(param_id).and_then(|b| b.node_id())
+    }
+
+    /// Find the node assigned to a type parameter.
+    ///
+    /// Returns `None` if the parameter is unbound or bound directly to a type.
+    pub fn get_type(&self, param_id: NodeId) -> Option<NodeId> {
+        self.find_type(param_id).and_then(|b| b.node_id())
+    }
+
+    /// Get the environments that the bindings refer to.
+    pub(crate) fn referenced_envs(&self) -> Vec<ParamEnv> {
+        let values = self.values.iter().flat_map(|(_, b)| match *b {
+            ParamEnvBinding::Indirect(x) => Some(x.env()),
+            _ => None,
+        });
+        let types = self.types.iter().flat_map(|(_, b)| match *b {
+            ParamEnvBinding::Indirect(x) => Some(x.env()),
+            _ => None,
+        });
+        values
+            .chain(types)
+            .chain(self.intfs.iter().map(|(_, x)| x.env()))
+            .collect()
+    }
+
+    /// Check whether a parameter fell back to its default.
+    ///
+    /// Returns `false` for parameters that are explicitly assigned or not
+    /// bound at all. Since environments are interned, the defaults of a
+    /// canonicalized environment are those of the first environment that
+    /// canonicalized to it.
+    pub fn is_defaulted(&self, param_id: NodeId) -> bool {
+        match (self.find_value(param_id), self.find_type(param_id)) {
+            (Some(ParamEnvBinding::Default(_)), _) | (_, Some(ParamEnvBinding::Default(_))) => true,
+            _ => self.defaulted.contains(&param_id),
+        }
+    }
+
+    /// Check whether a parameter has been left open for a later completion.
+    ///
+    /// Only partial environments, see [`ParamEnvSource::Partial`], leave
+    /// parameters open. Unlike a defaulted parameter, an open parameter has no
+    /// binding at all and cannot be evaluated until the environment has been
+    /// completed with [`ParamEnv::complete`].
+    pub fn is_open(&self, param_id: NodeId) -> bool {
+        self.open.binary_search(&param_id).is_ok()
+    }
+
+    /// Get the parameters that have been left open for a later completion.
+    pub fn open_params(&self) -> &[NodeId] {
+        &self.open
+    }
+
+    /// Check whether this is a partial environment, which leaves some
+    /// parameters open.
+    pub fn is_partial(&self) -> bool {
+        !self.open.is_empty()
+    }
+
+    /// Iterate over the value parameter bindings.
+    pub fn values(&self) -> impl Iterator<Item = (NodeId, ParamEnvBinding<Value<'t>>)> + '_ {
+        self.values.iter().cloned()
+    }
+
+    /// Iterate over the type parameter bindings.
+    pub fn types(
+        &self,
+    ) -> impl Iterator<Item = (NodeId, ParamEnvBinding<&'t UnpackedType<'t>>)> + '_ {
+        self.types.iter().cloned()
+    }
+
+    /// Get the total number of value, type, and interface bindings.
+    pub fn num_bindings(&self) -> usize {
+        self.values.len() + self.types.len() + self.intfs.len()
+    }
+
+    /// Get the module or interface that generated the environment.
+    pub fn module(&self) -> Option<NodeId> {
+        self.module
+    }
+
+    /// Associate the environment with a module or interface.
+    pub(crate) fn set_module(&mut self, module: NodeId) {
+        self.module = Some(module);
+    }
+
+    /// Get the span of the code that produced the environment, if known.
+    ///
+    /// Since environments are interned, this is the span of the first
+    /// instantiation or defparam that produced these bindings.
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
     /// Find the parametrization of an interface port.
     pub fn find_interface(&self, node_id: NodeId) -> Option<NodeEnvId> {
         self.intfs
@@ -122,13 +292,321 @@ impl<'t> ParamEnvData<'t> {
     /// Assign a value to a node.
     pub fn set_value(&mut self, node_id: NodeId, value: Value<'t>) {
         self.values.retain(|&(n, _)| n != node_id);
-        self.values.push((node_id, ParamEnvBinding::Direct(value)));
+        let index = match self.values.binary_search_by_key(&node_id, |&(n, _)| n) {
+            Ok(index) | Err(index) => index,
+        };
+        self.values
+            .insert(index, (node_id, ParamEnvBinding::Direct(value)));
+    }
+
+    /// Check the invariants of the environment.
+    ///
+    /// The value and type bindings must each be sorted by parameter, bind
+    /// every parameter at most once, and no parameter may be bound both as a
+    /// value and as a type. The open parameters must be sorted and must not be
+    /// bound. Returns a description of the first violation.
+    #[cfg(debug_assertions)]
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        fn check_sorted<T>(
+            kind: &str,
+            bindings: &[(NodeId, T)],
+        ) -> std::result::Result<(), String> {
+            for pair in bindings.windows(2) {
+                if pair[0].0 == pair[1].0 {
+                    return Err(format!("{:?} has multiple {} bindings", pair[0].0, kind));
+                }
+                if pair[0].0 > pair[1].0 {
+                    return Err(format!(
+                        "{} bindings are not sorted: {:?} before {:?}",
+                        kind, pair[0].0, pair[1].0
+                    ));
+                }
+            }
+            Ok(())
+        }
+        check_sorted("value", &self.values)?;
+        check_sorted("type", &self.types)?;
+        let (mut values, mut types) = (self.values.iter().peekable(), self.types.iter().peekable());
+        while let (Some(&&(v, _)), Some(&&(t, _))) = (values.peek(), types.peek()) {
+            if v == t {
+                return Err(format!("{:?} is bound both as a value and as a type", v));
+            }
+            if v < t {
+                values.next();
+            } else {
+                types.next();
+            }
+        }
+        if self.open.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(format!("open parameters are not sorted: {:?}", self.open));
+        }
+        if let Some(&id) = self
+            .open
+            .iter()
+            .find(|&&id| self.find_value(id).is_some() || self.find_type(id).is_some())
+        {
+            return Err(format!("{:?} is both open and bound", id));
+        }
+        Ok(())
     }
 
     /// Add additional interface parametrizations.
     pub fn add_interfaces(&mut self, iter: impl IntoIterator<Item = (NodeId, NodeEnvId)>) {
         self.intfs.extend(iter);
     }
+
+    /// Check that the environment binds exactly the given parameters.
+    ///
+    /// `expected` and `expected_types` list the value and type parameters,
+    /// respectively, together with the node they are expected to be bound to.
+    /// Panics with a message naming the first differing parameter otherwise.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn assert_matches(
+        &self,
+        expected: &[(NodeId, NodeId)],
+        expected_types: &[(NodeId, NodeId)],
+    ) {
+        fn check<T>(
+            what: &str,
+            actual: &[(NodeId, ParamEnvBinding<T>)],
+            expected: &[(NodeId, NodeId)],
+        ) {
+            for &(param_id, node_id) in expected {
+                match actual.iter().find(|&&(id, _)| id == param_id) {
+                    Some((_, binding)) if binding.node_id() == Some(node_id) => (),
+                    Some((_, binding)) => panic!(
+                        "{} parameter {:?} is bound to {:?}, expected {:?}",
+                        what,
+                        param_id,
+                        binding.node_id(),
+                        node_id
+                    ),
+                    None => panic!(
+                        "{} parameter {:?} is unbound, expected {:?}",
+                        what, param_id, node_id
+                    ),
+                }
+            }
+            for (param_id, binding) in actual {
+                if !expected.iter().any(|&(id, _)| id == *param_id) {
+                    panic!(
+                        "{} parameter {:?} is unexpectedly bound to {:?}",
+                        what,
+                        param_id,
+                        binding.node_id()
+                    );
+                }
+            }
+        }
+        check("value", &self.values, expected);
+        check("type", &self.types, expected_types);
+    }
+}
+
+/// Serialization of parameter environments.
+///
+/// This allows environments to be persisted across runs, e.g. by a build
+/// cache. The serialized node ids and environments are only meaningful within
+/// a stable node numbering, i.e. when the same sources are parsed in the same
+/// order, and the referenced environments have been interned again. Bindings
+/// directly to a value or type point into the arenas of a context and cannot
+/// be serialized. The span of an environment is not serialized.
+#[cfg(feature = "serde")]
+mod serialize {
+    use super::*;
+    use serde::{ser, Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    enum BindingRepr {
+        Indirect(u32, u32),
+        Default(u32),
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct DataRepr {
+        module: Option<u32>,
+        values: Vec<(u32, BindingRepr)>,
+        types: Vec<(u32, BindingRepr)>,
+        intfs: Vec<(u32, u32, u32)>,
+        defaulted: Vec<u32>,
+        #[serde(default)]
+        open: Vec<u32>,
+    }
+
+    fn to_repr<T>(bindings: &[(NodeId, ParamEnvBinding<T>)]) -> Option<Vec<(u32, BindingRepr)>> {
+        bindings
+            .iter()
+            .map(|(id, binding)| {
+                let repr = match *binding {
+                    ParamEnvBinding::Direct(_) => return None,
+                    ParamEnvBinding::Indirect(x) => {
+                        BindingRepr::Indirect(x.id().as_u32(), x.env().0)
+                    }
+                    ParamEnvBinding::Default(x) => BindingRepr::Default(x.as_u32()),
+                };
+                Some((id.as_u32(), repr))
+            })
+            .collect()
+    }
+
+    fn from_repr<T>(bindings: Vec<(u32, BindingRepr)>) -> Vec<(NodeId, ParamEnvBinding<T>)> {
+        bindings
+            .into_iter()
+            .map(|(id, repr)| {
+                let binding = match repr {
+                    BindingRepr::Indirect(x, env) => {
+                        ParamEnvBinding::Indirect(NodeId::from_u32(x).env(ParamEnv(env)))
+                    }
+                    BindingRepr::Default(x) => ParamEnvBinding::Default(NodeId::from_u32(x)),
+                };
+                (NodeId::from_u32(id), binding)
+            })
+            .collect()
+    }
+
+    impl Serialize for ParamEnvData<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+            let error = || ser::Error::custom("direct bindings cannot be serialized");
+            DataRepr {
+                module: self.module.map(|id| id.as_u32()),
+                values: to_repr(&self.values).ok_or_else(error)?,
+                types: to_repr(&self.types).ok_or_else(error)?,
+                intfs: self
+                    .intfs
+                    .iter()
+                    .map(|(id, x)| (id.as_u32(), x.id().as_u32(), x.env().0))
+                    .collect(),
+                defaulted: self.defaulted.iter().map(|id| id.as_u32()).collect(),
+                open: self.open.iter().map(|id| id.as_u32()).collect(),
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for ParamEnvData<'_> {
+        fn deserialize<D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> std::result::Result<Self, D::Error> {
+            let repr = DataRepr::deserialize(deserializer)?;
+            Ok(ParamEnvData {
+                module: repr.module.map(NodeId::from_u32),
+                values: from_repr(repr.values),
+                types: from_repr(repr.types),
+                intfs: repr
+                    .intfs
+                    .into_iter()
+                    .map(|(id, x, env)| {
+                        (NodeId::from_u32(id), NodeId::from_u32(x).env(ParamEnv(env)))
+                    })
+                    .collect(),
+                span: None,
+                defaulted: repr.defaulted.into_iter().map(NodeId::from_u32).collect(),
+                open: repr.open.into_iter().map(NodeId::from_u32).collect(),
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn roundtrip() {
+            let id = NodeId::from_u32;
+            let data = ParamEnvData::builder()
+                .module(id(1))
+                .add_value(id(4), ParamEnvBinding::Indirect(id(7).env(ParamEnv(2))))
+                .unwrap()
+                .add_value(id(3), ParamEnvBinding::Default(id(8)))
+                .unwrap()
+                .add_type(id(5), ParamEnvBinding::Default(id(9)))
+                .unwrap()
+                .build();
+            let json = serde_json::to_string(&data).unwrap();
+            let back: ParamEnvData = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, data);
+            assert_eq!(back.module(), Some(id(1)));
+            assert_eq!(
+                back.find_value(id(3)),
+                Some(ParamEnvBinding::Default(id(8)))
+            );
+            assert_eq!(back.referenced_envs(), vec![ParamEnv(2)]);
+        }
+
+        #[test]
+        fn direct_bindings_are_rejected() {
+            let mut data = ParamEnvData::default();
+            data.types.push((
+                NodeId::from_u32(1),
+                ParamEnvBinding::Direct(UnpackedType::make_error()),
+            ));
+            assert!(serde_json::to_string(&data).is_err());
+        }
+    }
+}
+
+/// A builder for parameter environments.
+///
+/// Use `ParamEnvData::builder()` to create one. This allows environments to be
+/// constructed without knowledge of how the bindings are stored internally.
+#[derive(Debug, Default)]
+pub struct ParamEnvDataBuilder<'t> {
+    data: ParamEnvData<'t>,
+}
+
+impl<'t> ParamEnvDataBuilder<'t> {
+    /// Set the module or interface that generates the environment.
+    pub fn module(&mut self, module: NodeId) -> &mut Self {
+        self.data.module = Some(module);
+        self
+    }
+
+    /// Set the span of the code that produces the environment.
+    pub fn span(&mut self, span: Span) -> &mut Self {
+        self.data.span = Some(span);
+        self
+    }
+
+    /// Bind a value parameter.
+    ///
+    /// Fails if the parameter has already been bound.
+    pub fn add_value(
+        &mut self,
+        param_id: NodeId,
+        assign: ParamEnvBinding<Value<'t>>,
+    ) -> Result<&mut Self> {
+        if self.data.find_value(param_id).is_some() {
+            return Err(());
+        }
+        self.data.values.push((param_id, assign));
+        Ok(self)
+    }
+
+    /// Bind a type parameter.
+    ///
+    /// Fails if the parameter has already been bound.
+    pub fn add_type(
+        &mut self,
+        param_id: NodeId,
+        assign: ParamEnvBinding<&'t UnpackedType<'t>>,
+    ) -> Result<&mut Self> {
+        if self.data.find_type(param_id).is_some() {
+            return Err(());
+        }
+        self.data.types.push((param_id, assign));
+        Ok(self)
+    }
+
+    /// Finish the environment.
+    ///
+    /// The bindings are brought into a canonical order, such that the result
+    /// does not depend on the order in which parameters were added.
+    pub fn build(&mut self) -> ParamEnvData<'t> {
+        let mut data = std::mem::take(&mut self.data);
+        data.types.sort_by_key(|&(id, _)| id);
+        data.values.sort_by_key(|&(id, _)| id);
+        data
+    }
 }
 
 /// A binding in a parameter environment.
@@ -138,163 +616,2767 @@ pub enum ParamEnvBinding<T> {
     Direct(T),
     /// An indirect binding, pointing at another node's type or value.
     Indirect(NodeEnvId),
+    /// A binding to the parameter's default, pointing at a node whose type or
+    /// value is evaluated in the environment that contains the binding. Names
+    /// in the default are resolved in the scope of the declaring module, which
+    /// includes the packages imported in the module header.
+    Default(NodeId),
+}
+
+impl<T> ParamEnvBinding<T> {
+    /// Get the node this binding points at, if any.
+    pub fn node_id(&self) -> Option<NodeId> {
+        match *self {
+            ParamEnvBinding::Direct(_) => None,
+            ParamEnvBinding::Indirect(id) => Some(id.id()),
+            ParamEnvBinding::Default(id) => Some(id),
+        }
+    }
+}
+
+/// The value or type a parameter evaluates to.
+///
+/// See [`Context::module_params_resolved`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolvedParamValue<'t> {
+    /// The folded value of a value parameter.
+    Value(Value<'t>),
+    /// The resolved type of a type parameter.
+    Type(&'t UnpackedType<'t>),
+}
+
+impl std::fmt::Display for ResolvedParamValue<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ResolvedParamValue::Value(value) => write!(f, "{}", value),
+            ResolvedParamValue::Type(ty) => write!(f, "{}", ty),
+        }
+    }
+}
+
+/// An override of a parameter by its hierarchical path.
+///
+/// See [`Context::register_param_override`].
+#[derive(Debug, Clone)]
+pub(crate) struct ParamOverride {
+    /// The names of the top-level module, the instances, and the parameter.
+    pub path: Vec<Name>,
+    /// The expression assigned to the parameter.
+    pub value: NodeId,
+    /// Whether the override has been applied to an instance.
+    pub matched: bool,
+}
+
+/// A naming scheme for parametrized instances.
+///
+/// Backends that flatten the design need a unique and deterministic name for
+/// every combination of module and parameter environment. Implementors only
+/// have to override [`mangle`](ParamEnvMangler::mangle) to customize the
+/// scheme; see [`DefaultParamEnvMangler`] for the default one.
+pub trait ParamEnvMangler {
+    /// Produce the name of `module` elaborated with `env`.
+    ///
+    /// The default concatenates the module name with the name and value of
+    /// every parameter, e.g. `fifo__W8__DEPTH16`. Characters that may not
+    /// appear in an identifier are replaced with underscores. If the
+    /// parameters cannot be resolved, the environment handle is used instead.
+    fn mangle<'a>(&self, cx: &impl Context<'a>, module: NodeId, env: ParamEnv) -> String {
+        let mut name = match cx.hir_of(module) {
+            Ok(HirNode::Module(m)) => m.name.value.to_string(),
+            Ok(HirNode::Interface(i)) => i.ast.name.value.to_string(),
+            _ => format!("{:?}", module),
+        };
+        if env == ParamEnv::EMPTY {
+            return name;
+        }
+        match cx.module_params_resolved(module, env) {
+            Ok(params) => {
+                for (param, value) in params {
+                    name.push_str("__");
+                    name.push_str(&param.to_string());
+                    name.extend(value.to_string().chars().filter_map(|c| match c {
+                        ' ' => None,
+                        c if c.is_ascii_alphanumeric() || c == '_' => Some(c),
+                        _ => Some('_'),
+                    }));
+                }
+            }
+            Err(()) => name.push_str(&format!("__{}", env)),
+        }
+        name
+    }
+}
+
+/// The default naming scheme for parametrized instances.
+///
+/// See [`ParamEnvMangler::mangle`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultParamEnvMangler;
+
+impl ParamEnvMangler for DefaultParamEnvMangler {}
+
+/// Statistics about the interned parameter environments.
+///
+/// The counters are maintained incrementally as environments are interned, so
+/// producing them is cheap. See [`Context::param_env_stats`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ParamEnvStats {
+    /// The number of distinct environments, including [`ParamEnv::EMPTY`].
+    pub envs: usize,
+    /// The total number of bindings stored across all distinct environments.
+    pub bindings: usize,
+    /// The number of instantiations of parameterless modules and interfaces,
+    /// which skip binding parameters altogether.
+    pub empty_hits: usize,
+    /// The number of errors reported in the parameter assignments of module
+    /// and interface instantiations.
+    ///
+    /// Drivers can compare this before and after elaborating part of a design
+    /// to decide whether to carry on with its siblings.
+    pub errors: usize,
+}
+
+/// The difference between two parameter environments.
+///
+/// See [`param_env_diff`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ParamEnvDiff {
+    /// The parameters that are only bound in the second environment.
+    pub added: Vec<NodeId>,
+    /// The parameters that are only bound in the first environment.
+    pub removed: Vec<NodeId>,
+    /// The parameters that are bound in both environments, but to different
+    /// types, values, or nodes.
+    pub changed: Vec<NodeId>,
+}
+
+impl ParamEnvDiff {
+    /// Check whether the two environments bind the same parameters.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compare the parameter bindings of two environments.
+///
+/// The bindings are compared by parameter id and the node or constant they
+/// are bound to. The parameter environments that indirect bindings are
+/// evaluated in are not considered, since their handles depend on the order in
+/// which environments were interned.
+pub fn param_env_diff<'t>(a: &ParamEnvData<'t>, b: &ParamEnvData<'t>) -> ParamEnvDiff {
+    let mut diff = ParamEnvDiff::default();
+    diff_bindings(&a.values, &b.values, &mut diff);
+    diff_bindings(&a.types, &b.types, &mut diff);
+    diff.added.sort();
+    diff.removed.sort();
+    diff.changed.sort();
+    diff
+}
+
+fn diff_bindings<T: PartialEq>(
+    a: &[(NodeId, ParamEnvBinding<T>)],
+    b: &[(NodeId, ParamEnvBinding<T>)],
+    diff: &mut ParamEnvDiff,
+) {
+    let same = |x: &ParamEnvBinding<T>, y: &ParamEnvBinding<T>| match (x, y) {
+        (ParamEnvBinding::Direct(x), ParamEnvBinding::Direct(y)) => x == y,
+        (ParamEnvBinding::Indirect(x), ParamEnvBinding::Indirect(y)) => x.id() == y.id(),
+        (ParamEnvBinding::Default(x), ParamEnvBinding::Default(y)) => x == y,
+        _ => false,
+    };
+    for (id, x) in a {
+        match b.iter().find(|(other, _)| other == id) {
+            Some((_, y)) if !same(x, y) => diff.changed.push(*id),
+            Some(_) => (),
+            None => diff.removed.push(*id),
+        }
+    }
+    for (id, _) in b {
+        if !a.iter().any(|(other, _)| other == id) {
+            diff.added.push(*id);
+        }
+    }
 }
 
 /// A location that implies a parameter environment.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ParamEnvSource<'hir> {
+    /// An instantiation of a module with the given parameter assignments.
     ModuleInst {
         module: Ref<'hir, hir::Module<'hir>>,
+        /// The instantiation that assigns the parameters.
+        inst: NodeId,
+        env: ParamEnv,
+        pos: &'hir [PosParam],
+        named: &'hir [NamedParam],
+        /// The environment of an enclosing level of the hierarchy. The
+        /// parameter assignments are evaluated in `env` layered over it, such
+        /// that they may refer to parameters bound higher up in the hierarchy,
+        /// e.g. by a `bind` directive.
+        parent_env: Option<ParamEnv>,
+    },
+    /// One element of an array of module instances, such as `foo u[3:0]()`.
+    ///
+    /// All elements share the parameter assignments of the instantiation.
+    /// SystemVerilog provides no name for the index of an element, such that
+    /// the assignments cannot depend on it and all elements share one
+    /// environment.
+    ModuleInstArray {
+        module: Ref<'hir, hir::Module<'hir>>,
+        /// The instantiation that assigns the parameters.
+        inst: NodeId,
         env: ParamEnv,
         pos: &'hir [PosParam],
         named: &'hir [NamedParam],
+        /// See `ModuleInst`.
+        parent_env: Option<ParamEnv>,
     },
+    /// An instantiation of an interface with the given parameter assignments,
+    /// either explicitly or as the type of an interface port.
     InterfaceInst {
         interface: Ref<'hir, hir::Interface<'hir>>,
         env: ParamEnv,
         pos: &'hir [PosParam],
         named: &'hir [NamedParam],
     },
+    /// A `defparam` overriding the parameter `target` in the environment
+    /// `base` with `value`, which is evaluated in `env`.
+    Defparam {
+        base: ParamEnv,
+        env: ParamEnv,
+        target: NodeId,
+        value: NodeId,
+    },
+    /// An iteration of a generate loop, binding the loop variable `genvar` in
+    /// the environment `base` to `value`, which is evaluated in `base`.
+    GenerateLoop {
+        base: ParamEnv,
+        genvar: NodeId,
+        value: NodeId,
+    },
+    /// A partial specialization of the module or interface `module`, e.g. to
+    /// precompile it with some of its parameters fixed.
+    ///
+    /// Binds every parameter listed in `fixed` to the node assigned to it,
+    /// which is evaluated in `env`. All other parameters are left open, see
+    /// [`ParamEnvData::is_open`], until the environment is completed.
+    Partial {
+        module: NodeId,
+        env: ParamEnv,
+        fixed: &'hir [(NodeId, NodeId)],
+    },
+    /// The completion of the partial environment `base`.
+    ///
+    /// Binds every open parameter listed in `more` to the node assigned to it,
+    /// which is evaluated in `env`. The remaining open parameters fall back to
+    /// their default. See [`ParamEnv::complete`].
+    Complete {
+        base: ParamEnv,
+        env: ParamEnv,
+        more: &'hir [(NodeId, NodeId)],
+    },
+}
+
+impl<'hir> ParamEnvSource<'hir> {
+    /// Create the source for an instantiation.
+    ///
+    /// `inst_id` may either refer to an instantiation target, i.e. the
+    /// `foo #(x)` in `foo #(x) bar(y);`, or to one of the instances created by
+    /// it, i.e. the `bar(y)`. The parameter assignments are evaluated in `env`.
+    pub fn from_inst(
+        cx: &impl Context<'hir>,
+        inst_id: NodeId,
+        env: ParamEnv,
+    ) -> Result<ParamEnvSource<'hir>> {
+        let inst_target = match cx.hir_of(inst_id)? {
+            HirNode::InstTarget(x) => x,
+            HirNode::Inst(x) => match cx.hir_of(x.target)? {
+                HirNode::InstTarget(x) => x,
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+        Ok(match cx.resolve_inst_target(inst_target.ast)? {
+            InstTarget::Module(node) => ParamEnvSource::ModuleInst {
+                module: Ref(cx.hir_of_module(node)?),
+                inst: inst_target.ast.id(),
+                env,
+                pos: &inst_target.pos_params,
+                named: &inst_target.named_params,
+                parent_env: cx.param_env_parent(env),
+            },
+            InstTarget::Interface(node) => ParamEnvSource::InterfaceInst {
+                interface: Ref(cx.hir_of_interface(node)?),
+                env,
+                pos: &inst_target.pos_params,
+                named: &inst_target.named_params,
+            },
+        })
+    }
 }
 
 /// Compute the parameter bindings for an instantiation.
+///
+/// Invalid parameter assignments are reported as diagnostics. See
+/// [`param_env_checked`] for a variant which returns them instead.
 #[moore_derive::query]
 pub(crate) fn param_env<'a>(cx: &impl Context<'a>, src: ParamEnvSource<'a>) -> Result<ParamEnv> {
-    match src {
-        ParamEnvSource::ModuleInst {
-            module,
-            env,
-            pos,
-            named,
-        } => param_env_from_instance(
-            cx,
-            module.ast,
-            module
-                .params
-                .iter()
-                .cloned()
-                .chain(module.block.params.iter().cloned())
-                .collect(),
-            env,
-            pos,
-            named,
-        ),
-        ParamEnvSource::InterfaceInst {
-            interface,
-            env,
-            pos,
-            named,
-        } => param_env_from_instance(
-            cx,
-            interface.ast,
-            interface
-                .params
-                .iter()
-                .flat_map(|p| match &p.kind {
-                    ast::ParamKind::Type(x) => {
-                        x.iter().map(|d| d.id()).collect::<Vec<_>>().into_iter()
-                    }
-                    ast::ParamKind::Value(x) => {
-                        x.iter().map(|d| d.id()).collect::<Vec<_>>().into_iter()
-                    }
-                })
-                .chain(interface.block.params.iter().cloned())
-                .collect(),
+    let (node, inst) = match src {
+        ParamEnvSource::ModuleInst { module, inst, .. }
+        | ParamEnvSource::ModuleInstArray { module, inst, .. } => (module.ast.id(), Some(inst)),
+        ParamEnvSource::InterfaceInst { interface, .. } => (interface.ast.id(), None),
+        ParamEnvSource::Partial { module, .. } => (module, None),
+        ParamEnvSource::Complete { base, .. } => (env_module(cx, base)?, None),
+        ParamEnvSource::Defparam {
+            base,
             env,
-            pos,
-            named,
-        ),
+            target,
+            value,
+        } => return param_env_from_defparam(cx, base, env, target, value),
+        ParamEnvSource::GenerateLoop {
+            base,
+            genvar,
+            value,
+        } => return param_env_from_genvar(cx, base, genvar, value),
+    };
+    match param_env_checked(cx, src)? {
+        Ok(env) => Ok(env),
+        Err(errors) => {
+            cx.tables().param_env_stats.borrow_mut().errors += errors.len();
+            for error in errors {
+                cx.emit(note_instance(cx, inst, error.to_diag(cx, node)));
+            }
+            Err(())
+        }
     }
 }
 
-fn param_env_from_instance<'a>(
+/// Check the values assigned to the typed value parameters of an environment.
+///
+/// Every value parameter with an explicit type that is assigned an expression
+/// must be able to hold the value of that expression. The parameter type may
+/// depend on the other parameters of the environment, and in turn on the ports
+/// of the instance being elaborated. This check is therefore performed once
+/// the instantiation target has been resolved, rather than while binding the
+/// parameters. Errors point at `inst`, if one is given.
+#[moore_derive::query]
+pub(crate) fn check_param_assignments<'a>(
     cx: &impl Context<'a>,
-    node: &'a dyn ast::AnyNode<'a>,
-    params: Vec<NodeId>,
     env: ParamEnv,
-    pos: &[PosParam],
-    named: &[NamedParam],
-) -> Result<ParamEnv> {
-    // Associate the positional and named assignments with the actual
-    // parameters of the module.
-    let param_iter = pos
-        .iter()
-        .enumerate()
-        .map(|(index, &(span, assign_id))| match params.get(index) {
-            Some(&param_id) => Ok((param_id, (assign_id, env))),
-            None => {
-                cx.emit(
-                    DiagBuilder2::error(format!("{} only has {} parameter(s)", node, params.len()))
-                        .span(span),
-                );
-                Err(())
-            }
-        })
-        .chain(named.iter().map(|&(_span, name, assign_id)| {
-            let names: Vec<_> = params
-                .iter()
-                .flat_map(|&id| match cx.ast_of(id) {
-                    Ok(AstNode::TypeParam(_, p)) => Some((p.name.value, id)),
-                    Ok(AstNode::ValueParam(_, p)) => Some((p.name.value, id)),
-                    Ok(_) => unreachable!(),
-                    Err(()) => None,
-                })
-                .collect();
-            match names
-                .iter()
-                .find(|&(param_name, _)| *param_name == name.value)
+    inst: Option<NodeId>,
+) -> Result<()> {
+    let data = cx.param_env_data(env);
+    if data.is_partial() {
+        return Ok(());
+    }
+    let node = match data.module() {
+        Some(module) => module,
+        None => return Ok(()),
+    };
+    let mut errors = vec![];
+    for (param_id, binding) in data.values() {
+        let assign_id = match (binding, cx.ast_of(param_id)?) {
+            (ParamEnvBinding::Indirect(assign_id), AstNode::ValueParam(_, decl))
+                if !decl.ty.is_implicit() =>
             {
-                Some(&(_, param_id)) => Ok((param_id, (assign_id, env))),
-                None => {
-                    cx.emit(
-                        DiagBuilder2::error(format!("no parameter `{}` in {}", name, node,))
-                            .span(name.span)
-                            .add_note(format!(
-                                "declared parameters are {}",
-                                names
-                                    .iter()
-                                    .map(|&(n, _)| format!("`{}`", n))
-                                    .collect::<Vec<_>>()
-                                    .join(", ")
-                            )),
-                    );
-                    Err(())
-                }
+                assign_id
             }
-        }));
-    let param_iter = param_iter
-        .collect::<Vec<_>>()
-        .into_iter()
-        .collect::<Result<Vec<_>>>()?
-        .into_iter();
-
-    // Split up type and value parameters.
-    let mut types = vec![];
-    let mut values = vec![];
-    for (param_id, assign_id) in param_iter {
-        let assign_id = match assign_id {
-            (Some(i), n) => i.env(n),
             _ => continue,
         };
-        match cx.ast_of(param_id)? {
-            AstNode::TypeParam(..) => {
-                cx.set_lowering_hint(assign_id.0, hir::Hint::Type);
-                types.push((param_id, ParamEnvBinding::Indirect(assign_id)))
-            }
-            AstNode::ValueParam(..) => {
-                cx.set_lowering_hint(assign_id.0, hir::Hint::Expr);
-                values.push((param_id, ParamEnvBinding::Indirect(assign_id)))
+        let span = cx.span(assign_id.id());
+        if is_unbounded_expr(cx, assign_id.id()) {
+            let ty = cx.param_type(param_id, env)?;
+            if !is_unbounded_compatible(ty) {
+                errors.push(ParamEnvError::UnboundedNotInteger {
+                    param: param_id,
+                    span,
+                    ty,
+                });
             }
+            continue;
+        }
+        let from = match cx.self_determined_type(assign_id.id(), assign_id.env()) {
+            Some(ty) => ty,
+            None => continue,
+        };
+        let to = cx.param_type(param_id, env)?;
+        if !is_assignment_compatible(from, to) {
+            errors.push(ParamEnvError::IncompatibleType {
+                param: param_id,
+                span,
+                from,
+                to,
+            });
+        }
+    }
+    if errors.is_empty() {
+        return Ok(());
+    }
+    cx.tables().param_env_stats.borrow_mut().errors += errors.len();
+    for error in errors {
+        cx.emit(note_instance(cx, inst, error.to_diag(cx, node)));
+    }
+    Err(())
+}
+
+/// Check the parameter declarations of a module or interface.
+///
+/// This validates the declarations themselves, independently of any
+/// instantiation, and reports problems at the declarations. No two parameters
+/// may have the same name, which includes local parameters shadowing regular
+/// ones. If every parameter has a default, the defaults of value parameters
+/// with an explicit type are checked to be compatible with that type.
+#[moore_derive::query]
+pub(crate) fn check_module_params<'a>(cx: &impl Context<'a>, module: NodeId) -> Result<()> {
+    let params = params_of(cx, module)?;
+    let mut failed = false;
+
+    // Make sure the parameter names are unique.
+    let mut declared: HashMap<Name, NodeId> = HashMap::new();
+    for &param_id in &params {
+        let ast = cx.ast_of(param_id)?;
+        let name = match ast {
+            AstNode::TypeParam(_, p) => p.name,
+            AstNode::ValueParam(_, p) => p.name,
             _ => unreachable!(),
+        };
+        if let Some(&previous) = declared.get(&name.value) {
+            let previous_ast = cx.ast_of(previous)?;
+            let msg = if ast.is_local_param() && !previous_ast.is_local_param() {
+                format!(
+                    "localparam `{}` shadows parameter `{}`",
+                    name.value, name.value
+                )
+            } else {
+                format!("parameter `{}` declared multiple times", name.value)
+            };
+            cx.emit(
+                DiagBuilder2::error(msg)
+                    .span(name.span)
+                    .add_note("Previous declaration was here:")
+                    .span(previous_ast.human_span()),
+            );
+            failed = true;
+            continue;
         }
+        declared.insert(name.value, param_id);
+    }
+    if failed {
+        return Err(());
     }
 
-    let env = cx.intern_param_env(ParamEnvData {
-        module: Some(node.id()),
-        types,
-        values,
+    // Check the defaults of typed value parameters. This is only possible if
+    // the defaults do not depend on parameters that have to be assigned.
+    let all_defaulted = params.iter().all(|&id| match cx.ast_of(id) {
+        Ok(AstNode::TypeParam(_, p)) => p.ty.is_some(),
+        Ok(AstNode::ValueParam(_, p)) => p.expr.is_some(),
+        _ => false,
+    });
+    if !all_defaulted {
+        return Ok(());
+    }
+    let env = cx.default_param_env();
+    for &param_id in &params {
+        let (decl, expr) = match cx.ast_of(param_id)? {
+            AstNode::ValueParam(_, p) => match p.expr {
+                Some(ref expr) if !p.ty.is_implicit() => (p, expr),
+                _ => continue,
+            },
+            _ => continue,
+        };
+        if is_unbounded_expr(cx, expr.id()) {
+            let ty = cx.param_type(param_id, env)?;
+            if !is_unbounded_compatible(ty) {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "`$` cannot be assigned to {} of type `{}`",
+                        decl, ty
+                    ))
+                    .span(expr.span())
+                    .add_note("`$` may only be assigned to parameters of integer type"),
+                );
+                failed = true;
+            }
+            continue;
+        }
+        let from = match cx.self_determined_type(expr.id(), env) {
+            Some(ty) => ty,
+            None => continue,
+        };
+        let to = cx.param_type(param_id, env)?;
+        if !is_assignment_compatible(from, to) {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "default of type `{}` cannot be assigned to {} of type `{}`",
+                    from, decl, to
+                ))
+                .span(expr.span()),
+            );
+            failed = true;
+        }
+    }
+    if failed {
+        return Err(());
+    }
+    Ok(())
+}
+
+/// Canonicalize a parameter environment.
+///
+/// Replaces the bindings of all parameters with the constant value or type
+/// they evaluate to. Environments which assign the same values through
+/// different expressions, e.g. `#(8)` and `#(4+4)`, thus canonicalize to the
+/// same environment. The original environment remains interned and should
+/// still be used for diagnostics, since it points at the assignments in the
+/// source. If any of the bindings fails to evaluate, `env` is returned as is.
+#[moore_derive::query]
+pub(crate) fn canonicalize_param_env<'a>(cx: &impl Context<'a>, env: ParamEnv) -> ParamEnv {
+    let mut data = cx.param_env_data(env).clone();
+    for (param_id, binding) in data.values.iter_mut() {
+        let value = cx.constant_value_of(*param_id, env);
+        if value.is_error() {
+            return env;
+        }
+        if let ParamEnvBinding::Default(_) = binding {
+            if !data.defaulted.contains(param_id) {
+                data.defaulted.push(*param_id);
+            }
+        }
+        *binding = ParamEnvBinding::Direct(value);
+    }
+    for (param_id, binding) in data.types.iter_mut() {
+        let ty = match cx.resolve_type_param(*param_id, env) {
+            Ok(ty) => ty,
+            Err(()) => return env,
+        };
+        if let ParamEnvBinding::Default(_) = binding {
+            if !data.defaulted.contains(param_id) {
+                data.defaulted.push(*param_id);
+            }
+        }
+        *binding = ParamEnvBinding::Direct(ty);
+    }
+    let canonical = cx.intern_param_env(data);
+    for context in cx.param_env_contexts(env) {
+        cx.add_param_env_context(canonical, context);
+    }
+    canonical
+}
+
+/// Find the cycles among parameter defaults.
+///
+/// `refs` lists the defaulted parameters in declaration order, together with
+/// the defaulted parameters their default refers to. Every cycle is reported
+/// once.
+fn find_default_cycles(refs: &[(NodeId, Vec<(Span, NodeId)>)]) -> Vec<Vec<NodeId>> {
+    let graph: HashMap<NodeId, Vec<NodeId>> = refs
+        .iter()
+        .map(|(id, refs)| (*id, refs.iter().map(|&(_, r)| r).collect()))
+        .collect();
+    let mut done = HashSet::new();
+    let mut cycles = vec![];
+    for &(id, _) in refs {
+        let mut stack = vec![];
+        visit_default(id, &graph, &mut stack, &mut done, &mut cycles);
+    }
+    cycles
+}
+
+fn visit_default(
+    id: NodeId,
+    graph: &HashMap<NodeId, Vec<NodeId>>,
+    stack: &mut Vec<NodeId>,
+    done: &mut HashSet<NodeId>,
+    cycles: &mut Vec<Vec<NodeId>>,
+) {
+    if let Some(pos) = stack.iter().position(|&s| s == id) {
+        cycles.push(stack[pos..].to_vec());
+        return;
+    }
+    if done.contains(&id) {
+        return;
+    }
+    stack.push(id);
+    for &next in graph.get(&id).into_iter().flatten() {
+        visit_default(next, graph, stack, done, cycles);
+    }
+    stack.pop();
+    done.insert(id);
+}
+
+/// Collect the parameters of a module, in declaration order.
+fn module_params(module: &hir::Module) -> Vec<NodeId> {
+    module
+        .params
+        .iter()
+        .cloned()
+        .chain(module.block.params.iter().cloned())
+        .collect()
+}
+
+/// Collect the parameters of an interface, in declaration order.
+fn interface_params(interface: &hir::Interface) -> Vec<NodeId> {
+    interface
+        .params
+        .iter()
+        .flat_map(|p| match &p.kind {
+            ast::ParamKind::Type(x) => x.iter().map(|d| d.id()).collect::<Vec<_>>().into_iter(),
+            ast::ParamKind::Value(x) => x.iter().map(|d| d.id()).collect::<Vec<_>>().into_iter(),
+        })
+        .chain(interface.block.params.iter().cloned())
+        .collect()
+}
+
+/// Collect the parameters of the module or interface with the given id.
+pub(crate) fn params_of<'a>(cx: &impl Context<'a>, node_id: NodeId) -> Result<Vec<NodeId>> {
+    match cx.hir_of(node_id)? {
+        HirNode::Module(module) => Ok(module_params(module)),
+        HirNode::Interface(interface) => Ok(interface_params(interface)),
+        _ => unreachable!(),
+    }
+}
+
+/// Pair the parameters of a module or interface with their names.
+///
+/// Parameters whose AST cannot be found are omitted.
+fn param_names<'a>(cx: &impl Context<'a>, params: &[NodeId]) -> Vec<(Name, NodeId)> {
+    params
+        .iter()
+        .flat_map(|&id| match cx.ast_of(id) {
+            Ok(AstNode::TypeParam(_, p)) => Some((p.name.value, id)),
+            Ok(AstNode::ValueParam(_, p)) => Some((p.name.value, id)),
+            Ok(_) => unreachable!(),
+            Err(()) => None,
+        })
+        .collect()
+}
+
+/// Determine which parameters the local parameters of a module derive from.
+///
+/// Maps every local parameter of the module or interface to the overridable
+/// parameters its value or type refers to, either directly or through other
+/// local parameters. The dependencies are listed in declaration order.
+#[moore_derive::query]
+pub(crate) fn local_param_deps<'a>(
+    cx: &impl Context<'a>,
+    module: NodeId,
+) -> Result<Arc<HashMap<NodeId, Vec<NodeId>>>> {
+    let params = params_of(cx, module)?;
+    let mut direct = HashMap::new();
+    for &id in &params {
+        let refs = match cx.ast_of(id)? {
+            ast if !ast.is_local_param() => continue,
+            AstNode::TypeParam(_, p) => {
+                let mut visitor = ParamRefVisitor::new(cx);
+                if let Some(ref ty) = p.ty {
+                    ty.accept(&mut visitor);
+                }
+                visitor.refs
+            }
+            AstNode::ValueParam(_, p) => {
+                let mut visitor = ParamRefVisitor::new(cx);
+                if let Some(ref expr) = p.expr {
+                    expr.accept(&mut visitor);
+                }
+                visitor.refs
+            }
+            _ => unreachable!(),
+        };
+        let refs: HashSet<NodeId> = refs.into_iter().map(|(_, id)| id).collect();
+        direct.insert(id, refs);
+    }
+
+    // Follow references to other local parameters until no new parameters are
+    // found. The visited set keeps cyclic definitions from looping forever;
+    // they are reported when the values are computed.
+    let mut deps = HashMap::new();
+    for &id in direct.keys() {
+        let mut visited = HashSet::new();
+        let mut todo = vec![id];
+        let mut found = HashSet::new();
+        while let Some(id) = todo.pop() {
+            if !visited.insert(id) {
+                continue;
+            }
+            for &ref_id in &direct[&id] {
+                if direct.contains_key(&ref_id) {
+                    todo.push(ref_id);
+                } else {
+                    found.insert(ref_id);
+                }
+            }
+        }
+        let found = params
+            .iter()
+            .cloned()
+            .filter(|id| found.contains(id))
+            .collect();
+        deps.insert(id, found);
+    }
+    Ok(Arc::new(deps))
+}
+
+/// Report a parameter that has been left open by a partial environment.
+///
+/// Open parameters have no binding, which must not be mistaken for falling
+/// back to the default. They cannot be evaluated before the environment has
+/// been completed.
+pub(crate) fn report_open_param<'a>(cx: &impl Context<'a>, param_id: NodeId, env: ParamEnv) {
+    cx.emit(
+        DiagBuilder2::error(format!(
+            "{} is left open by partial parameter environment {}",
+            cx.ast_for_id(param_id),
+            env
+        ))
+        .span(cx.span(param_id))
+        .add_note("Complete the environment before evaluating its parameters"),
+    );
+}
+
+/// Get the module or interface that generated a parameter environment.
+///
+/// Emits an error and fails if the environment is not associated with one.
+fn env_module<'a>(cx: &impl Context<'a>, env: ParamEnv) -> Result<NodeId> {
+    match cx.param_env_data(env).module() {
+        Some(module) => Ok(module),
+        None => {
+            cx.emit(DiagBuilder2::error(format!(
+                "parameter environment {} is not associated with a module",
+                env
+            )));
+            Err(())
+        }
+    }
+}
+
+/// Extract the operand of a `type(<expr>)` parameter assignment.
+///
+/// Returns `None` if the assignment is anything else, including a
+/// `type(<type>)` reference, which is simply another way to spell a type.
+/// Operands that cannot be disambiguated are left to the regular lowering of
+/// the assignment to report.
+fn type_ref_expr<'a>(
+    cx: &impl Context<'a>,
+    ast: &'a ast::TypeOrExpr<'a>,
+) -> Option<&'a ast::Expr<'a>> {
+    let ty = match *ast {
+        ast::TypeOrExpr::Type(ty) if ty.sign == ast::TypeSign::None && ty.dims.is_empty() => ty,
+        _ => return None,
+    };
+    match ty.kind.data {
+        ast::TypeRef(ref arg) => match cx.disamb_type_or_expr(Ref(arg)).ok()? {
+            ast::TypeOrExpr::Expr(expr) => Some(expr),
+            ast::TypeOrExpr::Type(_) => None,
+        },
+        _ => None,
+    }
+}
+
+/// The names of the parameters of a module or interface.
+///
+/// See [`param_name_table`].
+#[derive(Debug, Default)]
+pub(crate) struct ParamNameTable {
+    /// The parameters and their names, in declaration order. This is also the
+    /// order in which they are listed in diagnostics.
+    pub names: Vec<(Name, NodeId)>,
+    /// The parameter each name refers to. If multiple parameters have the same
+    /// name, which is reported separately, the first one is used.
+    pub by_name: HashMap<Name, NodeId>,
+}
+
+/// Build the table of parameter names of a module or interface.
+///
+/// The table only depends on the module, such that it is built once and then
+/// shared by all instantiations of the module.
+#[moore_derive::query]
+pub(crate) fn param_name_table<'a>(
+    cx: &impl Context<'a>,
+    module: NodeId,
+) -> Result<Arc<ParamNameTable>> {
+    let names = param_names(cx, &params_of(cx, module)?);
+    let mut by_name = HashMap::new();
+    for &(name, id) in &names {
+        by_name.entry(name).or_insert(id);
+    }
+    Ok(Arc::new(ParamNameTable { names, by_name }))
+}
+
+/// Reinterpret a parameter environment for a different module.
+///
+/// Moves every binding of `env` from a parameter of the module the environment
+/// was created for to the parameter of the same name in `new_module`. Assigned
+/// values and types are kept as they are, whereas parameters that fell back to
+/// their default now fall back to the default declared in `new_module`. Open
+/// parameters of a partial environment remain open. This is intended for tools
+/// that rename a module but keep its parameters.
+/// Interface port bindings are dropped, since they are added per instance once
+/// the ports have been connected.
+///
+/// Emits an error and fails if `env` is not associated with a module, or if a
+/// bound parameter has no counterpart of the same name and kind in
+/// `new_module`.
+#[moore_derive::query]
+pub(crate) fn remap_param_env<'a>(
+    cx: &impl Context<'a>,
+    env: ParamEnv,
+    new_module: NodeId,
+) -> Result<ParamEnv> {
+    let data = cx.param_env_data(env);
+    let old_module = env_module(cx, env)?;
+    if old_module == new_module {
+        return Ok(env);
+    }
+    let old_names: HashMap<NodeId, Name> = cx
+        .param_name_table(old_module)?
+        .names
+        .iter()
+        .map(|&(name, id)| (id, name))
+        .collect();
+    let new_names = cx.param_name_table(new_module)?;
+
+    // Find the counterpart of every bound parameter, and the default it falls
+    // back to in the new module.
+    let bound: BTreeSet<NodeId> = data
+        .values
+        .iter()
+        .map(|&(id, _)| id)
+        .chain(data.types.iter().map(|&(id, _)| id))
+        .chain(data.defaulted.iter().cloned())
+        .chain(data.open.iter().cloned())
+        .collect();
+    let mut mapping = HashMap::new();
+    let mut failed = false;
+    for id in bound {
+        let new_id = old_names
+            .get(&id)
+            .and_then(|name| new_names.by_name.get(name))
+            .cloned();
+        let counterpart = match (cx.ast_of(id), new_id.map(|new_id| cx.ast_of(new_id))) {
+            (Ok(AstNode::ValueParam(..)), Some(Ok(AstNode::ValueParam(_, p)))) => {
+                new_id.map(|new_id| (new_id, p.expr.as_ref().map(|e| e.id())))
+            }
+            (Ok(AstNode::TypeParam(..)), Some(Ok(AstNode::TypeParam(_, p)))) => {
+                new_id.map(|new_id| (new_id, p.ty.as_ref().map(|t| t.id())))
+            }
+            _ => None,
+        };
+        match counterpart {
+            Some(x) => {
+                mapping.insert(id, x);
+            }
+            None => {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "{} has no counterpart in {}",
+                        cx.ast_for_id(id),
+                        cx.ast_for_id(new_module)
+                    ))
+                    .span(cx.span(id))
+                    .add_note(format!(
+                        "{} must declare a parameter of the same name and kind",
+                        cx.ast_for_id(new_module)
+                    )),
+                );
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        return Err(());
+    }
+    let values = remap_bindings(cx, &data.values, &mapping, new_module);
+    let types = remap_bindings(cx, &data.types, &mapping, new_module);
+    let defaulted = data.defaulted.iter().map(|id| mapping[id].0).collect();
+    let mut open: Vec<_> = data.open.iter().map(|id| mapping[id].0).collect();
+    open.sort();
+
+    let new_env = cx.intern_param_env(ParamEnvData {
+        module: Some(new_module),
+        values: values?,
+        types: types?,
+        intfs: Default::default(),
+        span: data.span,
+        defaulted,
+        open,
+    });
+    for context in cx.param_env_contexts(env) {
+        cx.add_param_env_context(new_env, context);
+    }
+    Ok(new_env)
+}
+
+/// Move parameter bindings to the counterparts of the parameters.
+///
+/// `mapping` provides the counterpart of every bound parameter, and the default
+/// it declares, if any. Bindings to defaults are redirected to the default of
+/// the counterpart.
+fn remap_bindings<'a, T: Copy>(
+    cx: &impl Context<'a>,
+    bindings: &[(NodeId, ParamEnvBinding<T>)],
+    mapping: &HashMap<NodeId, (NodeId, Option<NodeId>)>,
+    new_module: NodeId,
+) -> Result<Vec<(NodeId, ParamEnvBinding<T>)>> {
+    let mut failed = false;
+    let mut remapped = Vec::with_capacity(bindings.len());
+    for &(id, binding) in bindings {
+        let (new_id, default) = mapping[&id];
+        let binding = match (binding, default) {
+            (ParamEnvBinding::Default(_), Some(default)) => ParamEnvBinding::Default(default),
+            (ParamEnvBinding::Default(_), None) => {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "{} has no default in {}",
+                        cx.ast_for_id(new_id),
+                        cx.ast_for_id(new_module)
+                    ))
+                    .span(cx.span(new_id))
+                    .add_note(format!(
+                        "{} falls back to its default in the original environment",
+                        cx.ast_for_id(id)
+                    ))
+                    .span(cx.span(id)),
+                );
+                failed = true;
+                continue;
+            }
+            (binding, _) => binding,
+        };
+        remapped.push((new_id, binding));
+    }
+    if failed {
+        return Err(());
+    }
+    remapped.sort_by_key(|&(id, _)| id);
+    Ok(remapped)
+}
+
+/// An error in the parameter assignments of an instantiation.
+///
+/// See [`param_env_checked`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamEnvError<'a> {
+    /// A positional assignment beyond the number of parameters.
+    TooManyPositional {
+        index: usize,
+        span: Span,
+        count: usize,
+    },
+    /// A positional assignment that follows a named assignment.
+    PositionalAfterNamed { span: Span, named: Span },
+    /// A named assignment to a local parameter.
+    LocalAssigned { param: NodeId, span: Span },
+    /// A partial environment fixing a node that is not a parameter of the
+    /// module.
+    ForeignParam { param: NodeId, span: Span },
+    /// A named assignment to a parameter that does not exist.
+    UnknownNamed {
+        name: Spanned<Name>,
+        declared: Vec<Name>,
+    },
+    /// A `type(<expr>)` assigned to a value parameter.
+    TypeRefToValue { param: NodeId, span: Span },
+    /// A parameter that is assigned more than once.
+    AssignedMultipleTimes {
+        param: NodeId,
+        span: Span,
+        previous: Span,
+    },
+    /// A type assigned to a value parameter, or an expression assigned to a
+    /// type parameter.
+    KindMismatch {
+        param: NodeId,
+        span: Span,
+        assigned_type: bool,
+    },
+    /// Parameter defaults that depend on each other in a cycle. The
+    /// parameters are listed in the order they depend on each other.
+    DefaultCycle { params: Vec<NodeId> },
+    /// A parameter default that refers to a parameter declared after it.
+    DefaultDependsOnUnassigned {
+        param: NodeId,
+        on: NodeId,
+        span: Span,
+    },
+    /// Parameters that are not assigned and have no default.
+    MissingDefault { params: Vec<NodeId>, span: Span },
+    /// A type that does not satisfy the restriction of a type parameter.
+    ViolatedRestriction {
+        param: NodeId,
+        span: Span,
+        restriction: Spanned<ast::TypeRestriction>,
+        ty: &'a UnpackedType<'a>,
+    },
+    /// A value assigned to a parameter whose type it cannot be converted to.
+    IncompatibleType {
+        param: NodeId,
+        span: Span,
+        from: &'a UnpackedType<'a>,
+        to: &'a UnpackedType<'a>,
+    },
+    /// The unbounded marker `$` assigned to a parameter whose type is not an
+    /// integer type.
+    UnboundedNotInteger {
+        param: NodeId,
+        span: Span,
+        ty: &'a UnpackedType<'a>,
+    },
+}
+
+/// Render the name of a parameter in backticks, for use in diagnostics.
+fn quoted_param_name<'a>(cx: &impl Context<'a>, id: NodeId) -> String {
+    match cx.ast_of(id) {
+        Ok(AstNode::TypeParam(_, p)) => format!("`{}`", p.name.value),
+        Ok(AstNode::ValueParam(_, p)) => format!("`{}`", p.name.value),
+        _ => format!("{:?}", id),
+    }
+}
+
+impl<'a> ParamEnvError<'a> {
+    /// Create a diagnostic describing the error.
+    ///
+    /// `node` is the module or interface being instantiated.
+    pub fn to_diag(&self, cx: &impl Context<'a>, node: NodeId) -> DiagBuilder2 {
+        match *self {
+            // A positional assignment to a module without parameters is most
+            // likely a port connection placed in the wrong list.
+            ParamEnvError::TooManyPositional { span, count: 0, .. } => {
+                DiagBuilder2::error(format!(
+                    "{} declares no parameters; did you mean to connect ports?",
+                    cx.ast_for_id(node)
+                ))
+                .span(span)
+                .add_note(format!("{} declared here:", cx.ast_for_id(node)))
+                .span(cx.span(node))
+            }
+            ParamEnvError::TooManyPositional { index, span, count } => {
+                DiagBuilder2::error(format!(
+                    "positional parameter #{} exceeds the {} declared parameters",
+                    index + 1,
+                    count
+                ))
+                .span(span)
+                .add_note(format!("{} declared here:", cx.ast_for_id(node)))
+                .span(cx.span(node))
+            }
+            ParamEnvError::PositionalAfterNamed { span, named } => {
+                DiagBuilder2::error("positional parameters must appear before named ones")
+                    .span(span)
+                    .add_note("First named parameter was here:")
+                    .span(named)
+            }
+            ParamEnvError::LocalAssigned { param, span } => {
+                DiagBuilder2::error(format!("{} cannot be overridden", cx.ast_for_id(param)))
+                    .span(span)
+                    .add_note(format!(
+                        "{} declared as localparam here:",
+                        cx.ast_for_id(param)
+                    ))
+                    .span(cx.span(param))
+            }
+            ParamEnvError::ForeignParam { param, span } => DiagBuilder2::error(format!(
+                "{} is not a parameter of {}",
+                cx.ast_for_id(param),
+                cx.ast_for_id(node)
+            ))
+            .span(span),
+            ParamEnvError::UnknownNamed { name, ref declared } => {
+                let d = DiagBuilder2::error(format!(
+                    "no parameter `{}` in {}",
+                    name,
+                    cx.ast_for_id(node)
+                ))
+                .span(name.span)
+                .add_note(format!(
+                    "declared parameters are {}",
+                    declared
+                        .iter()
+                        .map(|n| format!("`{}`", n))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+                let name = name.value.to_string();
+                match declared
+                    .iter()
+                    .find(|n| n.to_string().eq_ignore_ascii_case(&name))
+                {
+                    Some(n) => d.add_note(format!(
+                        "parameter names are case-sensitive; did you mean `{}`?",
+                        n
+                    )),
+                    None => d,
+                }
+            }
+            ParamEnvError::AssignedMultipleTimes {
+                param,
+                span,
+                previous,
+            } => DiagBuilder2::error(format!("{} assigned multiple times", cx.ast_for_id(param)))
+                .span(span)
+                .add_note("Previous assignment was here:")
+                .span(previous),
+            ParamEnvError::KindMismatch {
+                param,
+                span,
+                assigned_type,
+            } => DiagBuilder2::error(format!(
+                "{} assigned to {}",
+                if assigned_type { "type" } else { "expression" },
+                cx.ast_for_id(param)
+            ))
+            .span(span)
+            .add_note(format!("{} declared here:", cx.ast_for_id(param)))
+            .span(cx.span(param)),
+            ParamEnvError::TypeRefToValue { param, span } => DiagBuilder2::error(format!(
+                "type reference assigned to {}",
+                cx.ast_for_id(param)
+            ))
+            .span(span)
+            .add_note("`type(...)` denotes the type of an expression, not its value")
+            .add_note(format!("{} declared here:", cx.ast_for_id(param)))
+            .span(cx.span(param)),
+            ParamEnvError::DefaultDependsOnUnassigned { param, on, span } => {
+                DiagBuilder2::error(format!(
+                    "default of {} depends on {}, which has not been assigned yet",
+                    cx.ast_for_id(param),
+                    cx.ast_for_id(on)
+                ))
+                .span(span)
+                .add_note("Parameter defaults may only refer to parameters declared before them")
+            }
+            ParamEnvError::DefaultCycle { ref params } => {
+                let chain: Vec<_> = params
+                    .iter()
+                    .chain(params.first())
+                    .map(|&id| quoted_param_name(cx, id))
+                    .collect();
+                let mut d = DiagBuilder2::error(format!(
+                    "cyclic parameter defaults: {}",
+                    chain.join(" -> ")
+                ))
+                .span(cx.span(params[0]));
+                for &id in &params[1..] {
+                    d = d
+                        .add_note(format!("{} declared here:", cx.ast_for_id(id)))
+                        .span(cx.span(id));
+                }
+                d.add_note(format!(
+                    "One of them needs to be assigned when instantiating {}",
+                    cx.ast_for_id(node)
+                ))
+            }
+            ParamEnvError::MissingDefault { ref params, span } => {
+                let names: Vec<_> = params.iter().map(|&id| quoted_param_name(cx, id)).collect();
+                let msg = if names.len() == 1 {
+                    format!("parameter {} has no default and was not assigned", names[0])
+                } else {
+                    format!(
+                        "parameters {} have no default and were not assigned",
+                        names.join(", ")
+                    )
+                };
+                let mut d = DiagBuilder2::error(msg).span(span);
+                for &id in params {
+                    d = d
+                        .add_note(format!("{} declared here:", cx.ast_for_id(id)))
+                        .span(cx.span(id));
+                }
+                d.add_note(format!(
+                    "Needs to be assigned when instantiating {}",
+                    cx.ast_for_id(node)
+                ))
+            }
+            ParamEnvError::ViolatedRestriction {
+                param,
+                span,
+                restriction,
+                ty,
+            } => {
+                let kind = match restriction.value {
+                    ast::TypeRestriction::Enum => "an enum",
+                    ast::TypeRestriction::Struct => "a struct",
+                    ast::TypeRestriction::Union => "a union",
+                };
+                DiagBuilder2::error(format!(
+                    "{} requires {} type, but `{}` is not {}",
+                    cx.ast_for_id(param),
+                    kind,
+                    ty,
+                    kind
+                ))
+                .span(span)
+                .add_note("Restriction declared here:")
+                .span(restriction.span)
+            }
+            ParamEnvError::IncompatibleType {
+                param,
+                span,
+                from,
+                to,
+            } => DiagBuilder2::error(format!(
+                "value of type `{}` cannot be assigned to {} of type `{}`",
+                from,
+                cx.ast_for_id(param),
+                to
+            ))
+            .span(span)
+            .add_note(format!("{} declared here:", cx.ast_for_id(param)))
+            .span(cx.span(param)),
+            ParamEnvError::UnboundedNotInteger { param, span, ty } => DiagBuilder2::error(format!(
+                "`$` cannot be assigned to {} of type `{}`",
+                cx.ast_for_id(param),
+                ty
+            ))
+            .span(span)
+            .add_note("`$` may only be assigned to parameters of integer type")
+            .add_note(format!("{} declared here:", cx.ast_for_id(param)))
+            .span(cx.span(param)),
+        }
+    }
+}
+
+/// Compute the parameter environment of an instantiation, without reporting
+/// invalid parameter assignments.
+///
+/// This is the same as the `param_env` query, but returns any problems with
+/// the parameter assignments of a module or interface instantiation as
+/// structured errors instead of emitting diagnostics. Returns `Err(())` if the
+/// computation failed for other reasons, which have been reported already.
+/// Failing defparams are always reported.
+pub fn param_env_checked<'a>(
+    cx: &impl Context<'a>,
+    src: ParamEnvSource<'a>,
+) -> Result<std::result::Result<ParamEnv, Vec<ParamEnvError<'a>>>> {
+    match src {
+        ParamEnvSource::ModuleInst {
+            module,
+            inst,
+            env,
+            pos,
+            named,
+            parent_env,
+        } => {
+            #[cfg(debug_assertions)]
+            check_positional_count(cx, inst, pos.len());
+            let eval_env = match parent_env {
+                Some(parent) => layer_param_env(cx, env, parent, pos, named),
+                None => env,
+            };
+            let result = param_env_from_instance(
+                cx,
+                module.ast,
+                Some(inst),
+                module_params(*module),
+                eval_env,
+                pos,
+                named,
+            )?;
+            if let Ok(inner_env) = result {
+                cx.tables()
+                    .param_env_instances
+                    .borrow_mut()
+                    .entry(inner_env)
+                    .or_insert_with(Default::default)
+                    .insert(inst);
+                cx.tables()
+                    .param_env_parents
+                    .borrow_mut()
+                    .entry(inner_env)
+                    .or_insert(env);
+            }
+            Ok(result)
+        }
+        ParamEnvSource::ModuleInstArray {
+            module,
+            inst,
+            env,
+            pos,
+            named,
+            parent_env,
+        } => param_env_checked(
+            cx,
+            ParamEnvSource::ModuleInst {
+                module,
+                inst,
+                env,
+                pos,
+                named,
+                parent_env,
+            },
+        ),
+        ParamEnvSource::InterfaceInst {
+            interface,
+            env,
+            pos,
+            named,
+        } => param_env_from_instance(
+            cx,
+            interface.ast,
+            None,
+            interface_params(*interface),
+            env,
+            pos,
+            named,
+        ),
+        ParamEnvSource::Partial { module, env, fixed } => {
+            param_env_from_fixed(cx, module, None, env, fixed)
+        }
+        ParamEnvSource::Complete { base, env, more } => {
+            param_env_from_fixed(cx, env_module(cx, base)?, Some(base), env, more)
+        }
+        _ => cx.param_env(src).map(Ok),
+    }
+}
+
+/// Check that an instantiation has the same number of positional parameter
+/// assignments every time its environment is computed.
+///
+/// Instantiations inside a generate loop or instance array have their
+/// environment computed once per iteration or element. All of these share the
+/// assignments of the instantiation in the HIR, such that a differing count
+/// indicates malformed HIR. This is only checked in debug builds.
+#[cfg(debug_assertions)]
+fn check_positional_count<'a>(cx: &impl Context<'a>, inst: NodeId, count: usize) {
+    let previous = *cx
+        .tables()
+        .positional_counts
+        .borrow_mut()
+        .entry(inst)
+        .or_insert(count);
+    if previous != count {
+        cx.emit(
+            DiagBuilder2::warning(format!(
+                "instantiation has {} positional parameter assignments, but had {} before",
+                count, previous
+            ))
+            .span(cx.span(inst))
+            .add_note("All iterations of an instantiation should assign the same parameters"),
+        );
+    }
+}
+
+/// Collect the declarations referred to by a list of parameter assignments.
+fn assignment_refs<'a>(
+    cx: &impl Context<'a>,
+    pos: &[PosParam],
+    named: &[NamedParam],
+) -> HashSet<NodeId> {
+    let mut visitor = ParamRefVisitor::new(cx);
+    for assign_id in pos
+        .iter()
+        .map(|&(_, assign_id)| assign_id)
+        .chain(named.iter().map(|&(_, _, assign_id)| assign_id))
+        .flatten()
+    {
+        cx.ast_for_id(assign_id).accept(&mut visitor);
+    }
+    visitor.refs.into_iter().map(|(_, id)| id).collect()
+}
+
+/// Layer the environment `env` over the environment `parent`.
+///
+/// Every declaration that the parameter assignments refer to but which is not
+/// bound in `env` is bound as in `parent`. This allows the assignments to
+/// refer to parameters further up the hierarchy. Returns `env` itself if the
+/// assignments do not need anything from `parent`.
+fn layer_param_env<'a>(
+    cx: &impl Context<'a>,
+    env: ParamEnv,
+    parent: ParamEnv,
+    pos: &[PosParam],
+    named: &[NamedParam],
+) -> ParamEnv {
+    let refs = assignment_refs(cx, pos, named);
+    let parent_data = cx.param_env_data(parent);
+    let mut data = cx.param_env_data(env).clone();
+    let mut layered = false;
+    for (id, binding) in parent_data.values() {
+        if refs.contains(&id) && data.find_value(id).is_none() {
+            data.values.push((id, binding));
+            layered = true;
+        }
+    }
+    for (id, binding) in parent_data.types() {
+        if refs.contains(&id) && data.find_type(id).is_none() {
+            data.types.push((id, binding));
+            layered = true;
+        }
+    }
+    if !layered {
+        return env;
+    }
+    data.values.sort_by_key(|&(id, _)| id);
+    data.types.sort_by_key(|&(id, _)| id);
+    let layered_env = cx.intern_param_env(data);
+    for context in cx.param_env_contexts(env) {
+        cx.add_param_env_context(layered_env, context);
+    }
+    layered_env
+}
+
+/// Compute the parameter environment of a module or interface instance.
+///
+/// This is shared between modules and interfaces. `node` is the instantiated
+/// module or interface, and `params` lists its parameters in declaration
+/// order. `inst` is the instantiation that assigns the parameters, if any. The
+/// parameter assignments are evaluated in `env`.
+fn param_env_from_instance<'a>(
+    cx: &impl Context<'a>,
+    node: &'a dyn ast::AnyNode<'a>,
+    inst: Option<NodeId>,
+    params: Vec<NodeId>,
+    env: ParamEnv,
+    pos: &[PosParam],
+    named: &[NamedParam],
+) -> Result<std::result::Result<ParamEnv, Vec<ParamEnvError<'a>>>> {
+    let parameterless = params.is_empty() && pos.is_empty() && named.is_empty();
+    #[cfg(feature = "trace")]
+    let _span = tracing::debug_span!(
+        "param_env",
+        module = %node_name(node),
+        inst = %inst.map(|id| node_name(cx.ast_for_id(id))).unwrap_or_default(),
+        params = params.len(),
+        fast_path = parameterless,
+    )
+    .entered();
+
+    // Parameterless modules and interfaces have nothing to bind, and all of
+    // their instances share one environment.
+    if parameterless {
+        cx.tables().param_env_stats.borrow_mut().empty_hits += 1;
+        let env = cx.intern_param_env(ParamEnvData {
+            module: Some(node.id()),
+            span: Some(inst.map(|id| cx.span(id)).unwrap_or(node.span())),
+            ..Default::default()
+        });
+        cx.add_param_env_context(env, node.id());
+        return Ok(Ok(env));
+    }
+    cx.check_module_params(node.id())?;
+
+    // Look up the table of parameter names, such that the named assignments
+    // below can be matched without going back to the AST. The table is built
+    // once per module and shared by all its instantiations. Instantiations
+    // that only assign parameters by position, which is the common case, do
+    // not need it.
+    let names = if named.is_empty() {
+        Default::default()
+    } else {
+        cx.param_name_table(node.id())?
+    };
+
+    // Local parameters cannot be overridden, and do not count towards the
+    // index of positional assignments.
+    let mut locals = HashSet::new();
+    for &id in &params {
+        if cx
+            .ast_of(id)
+            .map(|ast| ast.is_local_param())
+            .unwrap_or(false)
+        {
+            locals.insert(id);
+        }
+    }
+    let overridable: Vec<_> = params
+        .iter()
+        .cloned()
+        .filter(|id| !locals.contains(id))
+        .collect();
+
+    // Associate the positional and named assignments with the actual
+    // parameters of the module. Every assignment is checked before bailing
+    // out, such that all superfluous or unknown parameters are reported at
+    // once.
+    let mut errors = vec![];
+    let mut assigns = vec![];
+    let first_named = named.iter().map(|&(span, ..)| span).min_by_key(|s| s.begin);
+    for (index, &(span, assign_id)) in pos.iter().enumerate() {
+        match first_named {
+            Some(named) if span.begin > named.begin => {
+                errors.push(ParamEnvError::PositionalAfterNamed { span, named })
+            }
+            _ => (),
+        }
+        match overridable.get(index) {
+            Some(&param_id) => assigns.push((param_id, span, (assign_id, env))),
+            None => errors.push(ParamEnvError::TooManyPositional {
+                index,
+                span,
+                count: overridable.len(),
+            }),
+        }
+    }
+    for &(span, name, assign_id) in named {
+        let target = names.by_name.get(&name.value).cloned();
+        if let Some(param_id) = target {
+            cx.tables()
+                .param_override_targets
+                .borrow_mut()
+                .insert(name.span, param_id);
+        }
+        match target {
+            Some(param_id) if locals.contains(&param_id) => {
+                errors.push(ParamEnvError::LocalAssigned {
+                    param: param_id,
+                    span,
+                })
+            }
+            Some(param_id) => assigns.push((param_id, span, (assign_id, env))),
+            None => errors.push(ParamEnvError::UnknownNamed {
+                name,
+                declared: names.names.iter().map(|&(n, _)| n).collect(),
+            }),
+        }
+    }
+    if !errors.is_empty() {
+        return Ok(Err(errors));
+    }
+
+    // Make sure no parameter is assigned more than once, either by repeating
+    // a named assignment or by assigning a name that was already assigned by
+    // position.
+    let mut first_assigned = HashMap::new();
+    for &(param_id, span, _) in &assigns {
+        match first_assigned.get(&param_id) {
+            Some(&previous) => errors.push(ParamEnvError::AssignedMultipleTimes {
+                param: param_id,
+                span,
+                previous,
+            }),
+            None => {
+                first_assigned.insert(param_id, span);
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Ok(Err(errors));
+    }
+    if cx.sess().has_verbosity(Verbosity::PARAMS) {
+        for &(span, name, _) in named {
+            if let Some(&param_id) = names.by_name.get(&name.value) {
+                trace_derived_locals(cx, node.id(), param_id, span)?;
+            }
+        }
+    }
+
+    bind_params(
+        cx,
+        node,
+        inst,
+        &params,
+        assigns,
+        first_assigned,
+        BindMode::Instance,
+    )
+}
+
+/// Compute a partial parameter environment, or complete one.
+///
+/// Binds the parameters listed in `fixed` of `module` to the node assigned to
+/// them, which is evaluated in `env`. If `base` is given, the open parameters
+/// of that partial environment are completed. Otherwise a partial environment
+/// is created, which leaves the parameters that are not listed open.
+fn param_env_from_fixed<'a>(
+    cx: &impl Context<'a>,
+    module: NodeId,
+    base: Option<ParamEnv>,
+    env: ParamEnv,
+    fixed: &[(NodeId, NodeId)],
+) -> Result<std::result::Result<ParamEnv, Vec<ParamEnvError<'a>>>> {
+    let params = params_of(cx, module)?;
+    cx.check_module_params(module)?;
+    let base = base.map(|base| cx.param_env_data(base));
+
+    // The parameters bound by the environment being completed count as
+    // assigned already.
+    let mut first_assigned = HashMap::new();
+    if let Some(base) = base {
+        let bound = base
+            .values()
+            .map(|(id, b)| (id, b.node_id()))
+            .chain(base.types().map(|(id, b)| (id, b.node_id())));
+        for (param_id, node_id) in bound {
+            let span = node_id
+                .map(|id| cx.span(id))
+                .or(base.span())
+                .unwrap_or(cx.span(param_id));
+            first_assigned.insert(param_id, span);
+        }
+    }
+
+    let mut errors = vec![];
+    let mut assigns = vec![];
+    for &(param_id, assign_id) in fixed {
+        let span = cx.span(assign_id);
+        if !params.contains(&param_id) {
+            errors.push(ParamEnvError::ForeignParam {
+                param: param_id,
+                span,
+            });
+            continue;
+        }
+        if cx.ast_of(param_id)?.is_local_param() {
+            errors.push(ParamEnvError::LocalAssigned {
+                param: param_id,
+                span,
+            });
+            continue;
+        }
+        match first_assigned.get(&param_id) {
+            Some(&previous) => errors.push(ParamEnvError::AssignedMultipleTimes {
+                param: param_id,
+                span,
+                previous,
+            }),
+            None => {
+                first_assigned.insert(param_id, span);
+                assigns.push((param_id, span, (Some(assign_id), env)));
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Ok(Err(errors));
+    }
+
+    let mode = match base {
+        Some(base) => BindMode::Complete(base),
+        None => BindMode::Partial,
+    };
+    bind_params(
+        cx,
+        cx.ast_for_id(module),
+        None,
+        &params,
+        assigns,
+        first_assigned,
+        mode,
+    )
+}
+
+/// How `bind_params` treats the parameters that are not assigned.
+#[derive(Clone, Copy)]
+enum BindMode<'a> {
+    /// Unassigned parameters fall back to their default.
+    Instance,
+    /// Unassigned parameters are left open.
+    Partial,
+    /// The bindings of a partial environment are carried over, and its open
+    /// parameters that remain unassigned fall back to their default.
+    Complete(&'a ParamEnvData<'a>),
+}
+
+/// Bind the parameters of a module or interface to their assignments.
+///
+/// This is shared between instantiations and partial environments. `params`
+/// lists the parameters of `node` in declaration order, and `assigns` the
+/// explicit assignments together with the environment they are evaluated in.
+/// The assignments must target distinct parameters that can be overridden.
+/// `first_assigned` maps every bound parameter to the span of its assignment.
+fn bind_params<'a>(
+    cx: &impl Context<'a>,
+    node: &'a dyn ast::AnyNode<'a>,
+    inst: Option<NodeId>,
+    params: &[NodeId],
+    assigns: Vec<(NodeId, Span, (Option<NodeId>, ParamEnv))>,
+    first_assigned: HashMap<NodeId, Span>,
+    mode: BindMode<'a>,
+) -> Result<std::result::Result<ParamEnv, Vec<ParamEnvError<'a>>>> {
+    let mut errors = vec![];
+
+    // Split up type and value parameters, starting with the ones bound by the
+    // environment being completed.
+    let mut types = vec![];
+    let mut values = vec![];
+    let mut assigned = HashSet::new();
+    if let BindMode::Complete(base) = mode {
+        types.extend(base.types());
+        values.extend(base.values());
+        assigned.extend(base.types().map(|(id, _)| id));
+        assigned.extend(base.values().map(|(id, _)| id));
+    }
+    for (param_id, span, assign_id) in assigns {
+        // Empty assignments such as the middle one in `#(8, , 16)` leave the
+        // parameter unassigned, such that it falls back to its default below.
+        let assign_id = match assign_id {
+            (Some(i), n) => i.env(n),
+            _ => continue,
+        };
+
+        // Make sure types are only assigned to type parameters, and
+        // expressions only to value parameters. Assignments that cannot be
+        // disambiguated are reported later on when they are lowered.
+        let assign_kind = match cx.ast_of(assign_id.id())? {
+            AstNode::TypeOrExpr(x) => cx.disamb_type_or_expr(Ref(x)).ok(),
+            _ => None,
+        };
+        let type_ref = assign_kind.and_then(|x| type_ref_expr(cx, x));
+        if let (AstNode::ValueParam(..), Some(_)) = (cx.ast_of(param_id)?, type_ref) {
+            errors.push(ParamEnvError::TypeRefToValue {
+                param: param_id,
+                span,
+            });
+            continue;
+        }
+        let assigned_type = match (cx.ast_of(param_id)?, assign_kind) {
+            (AstNode::TypeParam(..), Some(ast::TypeOrExpr::Expr(_))) => Some(false),
+            (AstNode::ValueParam(..), Some(ast::TypeOrExpr::Type(_))) => Some(true),
+            _ => None,
+        };
+        if let Some(assigned_type) = assigned_type {
+            errors.push(ParamEnvError::KindMismatch {
+                param: param_id,
+                span,
+                assigned_type,
+            });
+            continue;
+        }
+
+        match (cx.ast_of(param_id)?, type_ref) {
+            // A `type(<expr>)` assignment binds the type of the expression,
+            // which is evaluated in the environment of the instantiation.
+            (AstNode::TypeParam(..), Some(expr)) => {
+                let ty = cx.need_self_determined_type(expr.id(), assign_id.env());
+                if ty.is_error() {
+                    return Err(());
+                }
+                types.push((param_id, ParamEnvBinding::Direct(ty)))
+            }
+            (AstNode::TypeParam(..), None) => {
+                cx.set_lowering_hint(assign_id.0, hir::Hint::Type);
+                types.push((param_id, ParamEnvBinding::Indirect(assign_id)))
+            }
+            (AstNode::ValueParam(..), _) => {
+                cx.set_lowering_hint(assign_id.0, hir::Hint::Expr);
+                values.push((param_id, ParamEnvBinding::Indirect(assign_id)));
+            }
+            _ => unreachable!(),
+        }
+        trace_binding(cx, param_id, span, "this assignment");
+        assigned.insert(param_id);
+    }
+    if !errors.is_empty() {
+        return Ok(Err(errors));
+    }
+
+    // Fall back to the default of every parameter that has not been assigned
+    // explicitly, in declaration order. The defaults are evaluated in the
+    // environment we are about to create, such that they can refer to other
+    // parameters. To keep this well-defined, the defaults must not depend on
+    // each other in a cycle, and a default may only refer to parameters that
+    // are assigned explicitly or declared before it.
+    let mut default_refs = vec![];
+    let mut missing = vec![];
+    let mut open = vec![];
+    let mut defaulted = vec![];
+    for &param_id in params {
+        if assigned.contains(&param_id) {
+            continue;
+        }
+        if let BindMode::Partial = mode {
+            open.push(param_id);
+            continue;
+        }
+        let default: Option<&'a dyn ast::AnyNode<'a>> = match cx.ast_of(param_id)? {
+            AstNode::TypeParam(_, decl) => decl.ty.as_ref().map(|ty| {
+                types.push((param_id, ParamEnvBinding::Default(ty.id())));
+                defaulted.push(param_id);
+                ty as &dyn ast::AnyNode
+            }),
+            AstNode::ValueParam(_, decl) => decl.expr.as_ref().map(|expr| {
+                values.push((param_id, ParamEnvBinding::Default(expr.id())));
+                defaulted.push(param_id);
+                expr as &dyn ast::AnyNode
+            }),
+            _ => unreachable!(),
+        };
+        match default {
+            Some(default) => {
+                trace_binding(cx, param_id, default.span(), "its default");
+                let mut visitor = ParamRefVisitor::new(cx);
+                default.accept(&mut visitor);
+                let refs: Vec<_> = visitor
+                    .refs
+                    .into_iter()
+                    .filter(|&(_, ref_id)| params.contains(&ref_id) && !assigned.contains(&ref_id))
+                    .collect();
+                default_refs.push((param_id, refs));
+            }
+            None => missing.push(param_id),
+        }
+    }
+    if !missing.is_empty() {
+        errors.push(ParamEnvError::MissingDefault {
+            params: missing,
+            span: inst.map(|id| cx.span(id)).unwrap_or(node.span()),
+        });
+    }
+    let cycles = find_default_cycles(&default_refs);
+    let mut in_cycle = HashSet::new();
+    for cycle in cycles {
+        in_cycle.extend(cycle.iter().cloned());
+        errors.push(ParamEnvError::DefaultCycle { params: cycle });
+    }
+    let mut pinned = assigned.clone();
+    for (param_id, refs) in default_refs {
+        if !in_cycle.contains(&param_id) {
+            for (span, ref_id) in refs {
+                if pinned.contains(&ref_id) || in_cycle.contains(&ref_id) {
+                    continue;
+                }
+                errors.push(ParamEnvError::DefaultDependsOnUnassigned {
+                    param: param_id,
+                    on: ref_id,
+                    span,
+                });
+            }
+        }
+        pinned.insert(param_id);
+    }
+    if !errors.is_empty() {
+        return Ok(Err(errors));
+    }
+
+    // Keep the bindings in a canonical order, such that environments which
+    // only differ in the order of their assignments are interned as the same.
+    types.sort_by_key(|&(id, _)| id);
+    values.sort_by_key(|&(id, _)| id);
+    open.sort();
+
+    let env = cx.intern_param_env(ParamEnvData {
+        module: Some(node.id()),
+        types,
+        values,
         intfs: Default::default(),
+        span: Some(inst.map(|id| cx.span(id)).unwrap_or(node.span())),
+        defaulted,
+        open,
     });
     cx.add_param_env_context(env, node.id());
-    Ok(env)
+
+    // The checks below may depend on the open parameters of a partial
+    // environment. They are performed once the environment is completed.
+    if let BindMode::Partial = mode {
+        return Ok(Ok(env));
+    }
+
+    // Make sure the types bound to restricted type parameters are of the
+    // requested kind. The values assigned to parameters with an explicit type
+    // are checked separately in `check_param_assignments`, since the parameter
+    // type may refer back to the instance being elaborated.
+    for &param_id in params {
+        let (restriction, default) = match cx.ast_of(param_id)? {
+            AstNode::TypeParam(_, decl) => match decl.restriction {
+                Some(restriction) => (restriction, decl.ty.as_ref().map(|ty| ty.span())),
+                None => continue,
+            },
+            _ => continue,
+        };
+        let ty = cx.resolve_type_param(param_id, env)?;
+        if !satisfies_restriction(ty, restriction.value) {
+            errors.push(ParamEnvError::ViolatedRestriction {
+                param: param_id,
+                span: first_assigned
+                    .get(&param_id)
+                    .cloned()
+                    .or(default)
+                    .unwrap_or(cx.span(param_id)),
+                restriction,
+                ty,
+            });
+        }
+    }
+    if !errors.is_empty() {
+        return Ok(Err(errors));
+    }
+    Ok(Ok(env))
+}
+
+/// Check whether a value of type `from` can be implicitly converted to `to`.
+///
+/// Any two types that have a simple bit vector representation can be
+/// converted into each other by changing their size, sign, and domain.
+/// Strings can be packed into and unpacked from simple bit vectors. Reals can
+/// be converted into other reals and to and from integral types, by rounding
+/// to the nearest integer (see IEEE 1800-2017 §6.12.1 and §10.7).
+fn is_assignment_compatible<'a>(from: &UnpackedType<'a>, to: &UnpackedType<'a>) -> bool {
+    if from.is_error() || to.is_error() || from.is_strictly_identical(to) {
+        return true;
+    }
+    let from_integral = from.get_simple_bit_vector().is_some();
+    let to_integral = to.get_simple_bit_vector().is_some();
+    if from.is_real() || to.is_real() {
+        return (from.is_real() || from_integral) && (to.is_real() || to_integral);
+    }
+    (from.is_string() || from_integral) && (to.is_string() || to_integral)
+}
+
+/// Check whether an expression is the unbounded marker `$`.
+fn is_unbounded_expr<'a>(cx: &impl Context<'a>, id: NodeId) -> bool {
+    match cx.hir_of(id) {
+        Ok(HirNode::Expr(expr)) => expr.kind == hir::ExprKind::Unbounded,
+        _ => false,
+    }
+}
+
+/// Check whether the unbounded marker `$` can be assigned to a parameter of a
+/// type.
+///
+/// See IEEE 1800-2017 §6.20.2 "Value parameters". Only integer types may hold
+/// `$`, which excludes reals, strings, and unpacked types.
+fn is_unbounded_compatible(ty: &UnpackedType) -> bool {
+    ty.is_error() || ty.get_simple_bit_vector().is_some()
+}
+
+/// Check whether a type is of the kind a type parameter is restricted to.
+fn satisfies_restriction(ty: &UnpackedType, restriction: ast::TypeRestriction) -> bool {
+    if ty.is_error() {
+        return true;
+    }
+    match restriction {
+        ast::TypeRestriction::Enum => ty.get_enum().is_some(),
+        ast::TypeRestriction::Struct => match ty.get_struct() {
+            Some(s) => s.kind == ast::StructKind::Struct,
+            None => false,
+        },
+        ast::TypeRestriction::Union => match ty.get_struct() {
+            Some(s) => s.kind != ast::StructKind::Struct,
+            None => false,
+        },
+    }
+}
+
+/// Emit a note about a parameter binding if name tracing is enabled.
+fn trace_binding<'a>(cx: &impl Context<'a>, param_id: NodeId, span: Span, what: &str) {
+    if cx.sess().has_verbosity(Verbosity::NAMES) {
+        cx.emit(
+            DiagBuilder2::note("parameter binding")
+                .span(span)
+                .add_note(format!("Bound {} to {}", cx.ast_for_id(param_id), what)),
+        );
+    }
+}
+
+/// Point out the local parameters that recompute from an overridden parameter.
+///
+/// Local parameters cannot be overridden themselves, so users who expect to
+/// override a derived value have to override the parameters it derives from.
+fn trace_derived_locals<'a>(
+    cx: &impl Context<'a>,
+    module: NodeId,
+    param_id: NodeId,
+    span: Span,
+) -> Result<()> {
+    let deps = cx.local_param_deps(module)?;
+    let params = params_of(cx, module)?;
+    let derived: Vec<_> = params
+        .iter()
+        .filter(|id| deps.get(id).map(|d| d.contains(&param_id)).unwrap_or(false))
+        .map(|&id| quoted_param_name(cx, id))
+        .collect();
+    if !derived.is_empty() {
+        cx.emit(
+            DiagBuilder2::note("overridden parameter has derived localparams")
+                .span(span)
+                .add_note(format!(
+                    "Overriding {} also recomputes localparam {}",
+                    quoted_param_name(cx, param_id),
+                    derived.join(", ")
+                )),
+        );
+    }
+    Ok(())
+}
+
+/// A visitor that collects the declarations referred to by a parameter default
+/// or assignment.
+struct ParamRefVisitor<'cx, C> {
+    cx: &'cx C,
+    refs: Vec<(Span, NodeId)>,
+}
+
+impl<'cx, C> ParamRefVisitor<'cx, C> {
+    fn new(cx: &'cx C) -> Self {
+        ParamRefVisitor { cx, refs: vec![] }
+    }
+}
+
+impl<'a, 'cx, C> ParamRefVisitor<'cx, C>
+where
+    C: Context<'a>,
+    'a: 'cx,
+{
+    fn add(&mut self, name: Spanned<Name>, node: &'a dyn ast::AnyNode<'a>) {
+        let at = self.cx.scope_location(node);
+        if let Ok(Some(def)) = self.cx.resolve_local(name.value, at, false) {
+            self.refs.push((name.span, def.node.id()));
+        }
+    }
+}
+
+impl<'a, 'cx, C> ast::Visitor<'a> for ParamRefVisitor<'cx, C>
+where
+    C: Context<'a>,
+    'a: 'cx,
+{
+    fn pre_visit_expr(&mut self, node: &'a ast::Expr<'a>) -> bool {
+        match node.data {
+            ast::IdentExpr(ident) => {
+                self.add(ident, node);
+                false
+            }
+            _ => true,
+        }
+    }
+
+    fn pre_visit_type(&mut self, node: &'a ast::Type<'a>) -> bool {
+        match node.kind.data {
+            ast::NamedType(ident) => {
+                self.add(ident, node);
+                false
+            }
+            _ => true,
+        }
+    }
+}
+
+/// The name of a node, for tracing spans.
+#[cfg(feature = "trace")]
+fn node_name<'a>(node: &'a dyn ast::AnyNode<'a>) -> String {
+    node.get_name()
+        .map(|name| name.value.to_string())
+        .unwrap_or_default()
+}
+
+/// Add a note to a diagnostic that points at the instantiation which caused
+/// the parameter environment to be created, if there is one.
+fn note_instance<'a>(
+    cx: &impl Context<'a>,
+    inst: Option<NodeId>,
+    diag: DiagBuilder2,
+) -> DiagBuilder2 {
+    let inst = match inst {
+        Some(inst) => cx.ast_for_id(inst),
+        None => return diag,
+    };
+    let names = match inst.as_all() {
+        ast::AllNode::Inst(inst) => inst
+            .names
+            .iter()
+            .map(|n| format!("`{}`", n.name))
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => return diag,
+    };
+    diag.add_note(format!("while elaborating instance {}", names))
+        .span(inst.human_span())
+}
+
+fn param_env_from_defparam<'a>(
+    cx: &impl Context<'a>,
+    base: ParamEnv,
+    env: ParamEnv,
+    target: NodeId,
+    value: NodeId,
+) -> Result<ParamEnv> {
+    let mut data = cx.param_env_data(base).clone();
+
+    // Make sure the target actually is a parameter of the module or interface
+    // that generated the base environment.
+    let params = match data.module {
+        Some(id) => params_of(cx, id)?,
+        None => vec![],
+    };
+    if !params.contains(&target) {
+        let mut d = DiagBuilder2::error(format!(
+            "{} cannot be overridden by defparam",
+            cx.ast_for_id(target)
+        ))
+        .span(cx.span(value));
+        if let Some(id) = data.module {
+            d = d.add_note(format!("{} has no such parameter", cx.ast_for_id(id)));
+        }
+        cx.emit(d);
+        return Err(());
+    }
+    match cx.ast_of(target)? {
+        AstNode::ValueParam(..) => (),
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} cannot be overridden by defparam",
+                    cx.ast_for_id(target)
+                ))
+                .span(cx.span(value))
+                .add_note("Only value parameters can be overridden by defparam"),
+            );
+            return Err(());
+        }
+    }
+    if cx.ast_of(target)?.is_local_param() {
+        cx.emit(
+            DiagBuilder2::error(format!("{} cannot be overridden", cx.ast_for_id(target)))
+                .span(cx.span(value))
+                .add_note(format!(
+                    "{} declared as localparam here:",
+                    cx.ast_for_id(target)
+                ))
+                .span(cx.span(target)),
+        );
+        return Err(());
+    }
+
+    // Replace the existing binding of the parameter, if any. This also fixes
+    // the parameter if it has been left open by a partial environment.
+    cx.set_lowering_hint(value, hir::Hint::Expr);
+    data.values.retain(|&(id, _)| id != target);
+    data.open.retain(|&id| id != target);
+    data.defaulted.retain(|&id| id != target);
+    data.values
+        .push((target, ParamEnvBinding::Indirect(value.env(env))));
+    data.values.sort_by_key(|&(id, _)| id);
+    data.span = Some(cx.span(value));
+    let new_env = cx.intern_param_env(data);
+    for context in cx.param_env_contexts(base) {
+        cx.add_param_env_context(new_env, context);
+    }
+    Ok(new_env)
+}
+
+fn param_env_from_genvar<'a>(
+    cx: &impl Context<'a>,
+    base: ParamEnv,
+    genvar: NodeId,
+    value: NodeId,
+) -> Result<ParamEnv> {
+    let mut data = cx.param_env_data(base).clone();
+    data.values.retain(|&(id, _)| id != genvar);
+    data.defaulted.retain(|&id| id != genvar);
+    data.values
+        .push((genvar, ParamEnvBinding::Indirect(value.env(base))));
+    data.values.sort_by_key(|&(id, _)| id);
+    let new_env = cx.intern_param_env(data);
+    for context in cx.param_env_contexts(base) {
+        cx.add_param_env_context(new_env, context);
+    }
+    Ok(new_env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::Lexer, parser, preproc::Preprocessor, GlobalArenas, GlobalContext};
+    use moore_common::{
+        arenas::TypedArena, name::get_name_table, source::get_source_manager, Session,
+    };
+    use std::{
+        collections::hash_map::DefaultHasher,
+        hash::{Hash, Hasher},
+    };
+
+    /// The parameter assignments passed to partial and complete environments.
+    ///
+    /// These have to outlive the context, since the sources of environments
+    /// are kept by the query caches.
+    type Fixed = TypedArena<(NodeId, NodeId)>;
+
+    /// Parse `input` and run `f` on a context holding the design.
+    fn with_design(input: &str, f: impl for<'a> FnOnce(&GlobalContext<'a>)) {
+        with_fixed(input, |cx, _| f(cx))
+    }
+
+    /// Like `with_design`, but also pass an arena for parameter assignments.
+    fn with_fixed(input: &str, f: impl for<'a> FnOnce(&GlobalContext<'a>, &'a Fixed)) {
+        let fixed = TypedArena::new();
+        let sess = Session::new();
+        let arena = GlobalArenas::default();
+        let source = get_source_manager().add_anonymous(input);
+        let pp = Preprocessor::new(source, &[], &[]);
+        let ast = parser::parse(Lexer::new(pp), &arena.ast).unwrap();
+        let cx = GlobalContext::new(&sess, &arena);
+        cx.add_files(std::iter::once(&ast));
+        f(&cx, &fixed);
+    }
+
+    fn module<'a>(cx: &GlobalContext<'a>, name: &str) -> NodeId {
+        cx.find_module(get_name_table().intern(name, true)).unwrap()
+    }
+
+    fn param<'a>(cx: &GlobalContext<'a>, module: NodeId, name: &str) -> NodeId {
+        let name = get_name_table().intern(name, true);
+        params_of(cx, module)
+            .unwrap()
+            .into_iter()
+            .find(|&id| cx.ast_for_id(id).get_name().unwrap().value == name)
+            .unwrap()
+    }
+
+    /// Get the first instantiation target in a module.
+    fn inst_target<'a>(cx: &GlobalContext<'a>, module: NodeId) -> &'a hir::InstTarget<'a> {
+        inst_targets(cx, module)[0]
+    }
+
+    /// Get the instantiation targets in a module, in declaration order.
+    fn inst_targets<'a>(cx: &GlobalContext<'a>, module: NodeId) -> Vec<&'a hir::InstTarget<'a>> {
+        let hir = match cx.hir_of(module).unwrap() {
+            HirNode::Module(x) => x,
+            _ => unreachable!(),
+        };
+        hir.block
+            .insts
+            .iter()
+            .map(|&id| {
+                let inst = match cx.hir_of(id).unwrap() {
+                    HirNode::Inst(x) => x,
+                    _ => unreachable!(),
+                };
+                match cx.hir_of(inst.target).unwrap() {
+                    HirNode::InstTarget(x) => x,
+                    _ => unreachable!(),
+                }
+            })
+            .collect()
+    }
+
+    /// Compute the environment of an instantiation target outside of any
+    /// instance.
+    fn target_env<'a>(cx: &GlobalContext<'a>, target: &hir::InstTarget<'a>) -> ParamEnv {
+        cx.param_env(ParamEnvSource::from_inst(cx, target.ast.id(), ParamEnv::EMPTY).unwrap())
+            .unwrap()
+    }
+
+    /// Compute the environment of the first instantiation in a module.
+    fn inst_env<'a>(cx: &GlobalContext<'a>, module: NodeId) -> ParamEnv {
+        target_env(cx, inst_target(cx, module))
+    }
+
+    /// Get the node assigned to the `index`-th named parameter of an
+    /// instantiation target.
+    fn named_assign(target: &hir::InstTarget, index: usize) -> NodeId {
+        target.named_params[index].2.unwrap()
+    }
+
+    const DESIGN: &str = "
+        module A;
+            B #(.W(8), .D(2)) b();
+        endmodule
+        module B #(parameter int W = 1, parameter int D = W / 2, parameter int E = 3);
+        endmodule
+        module C #(parameter int W = 1);
+        endmodule
+    ";
+
+    const TYPED: &str = "
+        module A;
+            B #(.T(logic [3:0]), .W(2)) b();
+        endmodule
+        module B #(parameter type T = bit, parameter int W = 1, parameter type U = T);
+        endmodule
+    ";
+
+    #[test]
+    fn partial_completes_to_instance_env() {
+        with_fixed(DESIGN, |cx, fixed| {
+            let (a, b) = (module(cx, "A"), module(cx, "B"));
+            let target = inst_target(cx, a);
+            let direct = target_env(cx, target);
+            let partial = cx
+                .param_env(ParamEnvSource::Partial {
+                    module: b,
+                    env: ParamEnv::EMPTY,
+                    fixed: fixed.alloc_extend(vec![(param(cx, b, "W"), named_assign(target, 0))]),
+                })
+                .unwrap();
+            assert!(cx.param_env_data(partial).is_partial());
+            let more = fixed.alloc_extend(vec![(param(cx, b, "D"), named_assign(target, 1))]);
+            let complete = partial.complete(cx, ParamEnv::EMPTY, more).unwrap();
+            assert!(!cx.param_env_data(complete).is_partial());
+            assert_eq!(complete, direct);
+        });
+    }
+
+    #[test]
+    fn completing_fixed_param_fails() {
+        with_fixed(DESIGN, |cx, fixed| {
+            let (a, b) = (module(cx, "A"), module(cx, "B"));
+            let target = inst_target(cx, a);
+            let w = param(cx, b, "W");
+            let partial = cx
+                .param_env(ParamEnvSource::Partial {
+                    module: b,
+                    env: ParamEnv::EMPTY,
+                    fixed: fixed.alloc_extend(vec![(w, named_assign(target, 0))]),
+                })
+                .unwrap();
+            let errors = param_env_checked(
+                cx,
+                ParamEnvSource::Complete {
+                    base: partial,
+                    env: ParamEnv::EMPTY,
+                    more: fixed.alloc_extend(vec![(w, named_assign(target, 1))]),
+                },
+            )
+            .unwrap()
+            .unwrap_err();
+            match errors.as_slice() {
+                [ParamEnvError::AssignedMultipleTimes { param, .. }] => assert_eq!(*param, w),
+                _ => panic!("unexpected errors {:?}", errors),
+            }
+        });
+    }
+
+    #[test]
+    fn fixing_foreign_param_fails() {
+        with_fixed(DESIGN, |cx, fixed| {
+            let (a, b, c) = (module(cx, "A"), module(cx, "B"), module(cx, "C"));
+            let target = inst_target(cx, a);
+            let foreign = param(cx, c, "W");
+            let errors = param_env_checked(
+                cx,
+                ParamEnvSource::Partial {
+                    module: b,
+                    env: ParamEnv::EMPTY,
+                    fixed: fixed.alloc_extend(vec![(foreign, named_assign(target, 0))]),
+                },
+            )
+            .unwrap()
+            .unwrap_err();
+            match errors.as_slice() {
+                [ParamEnvError::ForeignParam { param, .. }] => assert_eq!(*param, foreign),
+                _ => panic!("unexpected errors {:?}", errors),
+            }
+        });
+    }
+
+    #[test]
+    fn evaluating_open_param_fails() {
+        with_design(DESIGN, |cx| {
+            let b = module(cx, "B");
+            let partial = cx
+                .param_env(ParamEnvSource::Partial {
+                    module: b,
+                    env: ParamEnv::EMPTY,
+                    fixed: &[],
+                })
+                .unwrap();
+            assert!(!cx.sess().failed());
+            let value = cx.constant_value_of(param(cx, b, "E"), partial);
+            assert!(value.is_error());
+            assert!(cx.sess().failed());
+        });
+    }
+
+    #[test]
+    fn open_params_differ_from_defaulted() {
+        with_design(DESIGN, |cx| {
+            let b = module(cx, "B");
+            let partial = cx
+                .param_env(ParamEnvSource::Partial {
+                    module: b,
+                    env: ParamEnv::EMPTY,
+                    fixed: &[],
+                })
+                .unwrap();
+            let complete = partial.complete(cx, ParamEnv::EMPTY, &[]).unwrap();
+            assert_ne!(partial, complete);
+            let (open, defaulted) = (cx.param_env_data(partial), cx.param_env_data(complete));
+            assert_eq!(open.open_params().len(), 3);
+            match defaulted.find_value(param(cx, b, "E")) {
+                Some(ParamEnvBinding::Default(_)) => (),
+                x => panic!("`E` should be defaulted, but is {:?}", x),
+            }
+            assert_ne!(open, defaulted);
+            let hash = |data: &ParamEnvData| {
+                let mut h = DefaultHasher::new();
+                data.hash(&mut h);
+                h.finish()
+            };
+            assert_ne!(hash(open), hash(defaulted));
+        });
+    }
+
+    const HIERARCHY: &str = "
+        module Top;
+            A #(.N(8)) a();
+        endmodule
+        module A #(parameter int N = 3);
+            B #(.W(N)) b();
+        endmodule
+        module B #(parameter int W = 1);
+        endmodule
+    ";
+
+    #[test]
+    fn parent_env_layers_assignments() {
+        with_design(HIERARCHY, |cx| {
+            let (top, a, b) = (module(cx, "Top"), module(cx, "A"), module(cx, "B"));
+            let env_a = cx
+                .param_env(
+                    ParamEnvSource::from_inst(cx, inst_target(cx, top).ast.id(), ParamEnv::EMPTY)
+                        .unwrap(),
+                )
+                .unwrap();
+            let target = inst_target(cx, a);
+            match ParamEnvSource::from_inst(cx, target.ast.id(), env_a).unwrap() {
+                ParamEnvSource::ModuleInst { parent_env, .. } => {
+                    assert_eq!(parent_env, Some(ParamEnv::EMPTY))
+                }
+                src => panic!("unexpected source {:?}", src),
+            }
+
+            // Evaluate the assignment of `B` outside of any instance of `A`,
+            // once on its own and once layered over an instance of `A`.
+            let module = match cx.hir_of(b).unwrap() {
+                HirNode::Module(x) => Ref(x),
+                _ => unreachable!(),
+            };
+            let env_b = |parent_env| {
+                cx.param_env(ParamEnvSource::ModuleInst {
+                    module,
+                    inst: target.ast.id(),
+                    env: ParamEnv::EMPTY,
+                    pos: &target.pos_params,
+                    named: &target.named_params,
+                    parent_env,
+                })
+                .unwrap()
+            };
+            let w = param(cx, b, "W");
+            let value = |env| cx.constant_int_value_of(w, env).unwrap().clone();
+            assert_eq!(value(env_b(None)), num::BigInt::from(3));
+            assert_eq!(value(env_b(Some(env_a))), num::BigInt::from(8));
+        });
+    }
+
+    #[test]
+    fn defaulted_params_survive_canonicalization() {
+        with_design(DESIGN, |cx| {
+            let (a, b) = (module(cx, "A"), module(cx, "B"));
+            let env = inst_env(cx, a);
+            let (w, e) = (param(cx, b, "W"), param(cx, b, "E"));
+            let data = cx.param_env_data(env);
+            assert!(!data.is_defaulted(w));
+            assert!(data.is_defaulted(e));
+            assert_eq!(data.defaulted, vec![e]);
+
+            let canonical = cx.canonicalize_param_env(env);
+            assert_ne!(canonical, env);
+            let data = cx.param_env_data(canonical);
+            match data.find_value(e) {
+                Some(ParamEnvBinding::Direct(_)) => (),
+                x => panic!("`E` should be folded, but is {:?}", x),
+            }
+            assert!(!data.is_defaulted(w));
+            assert!(data.is_defaulted(e));
+            assert_eq!(data.defaulted, vec![e]);
+        });
+    }
+
+    #[test]
+    fn defparam_clears_defaulted() {
+        with_design(DESIGN, |cx| {
+            let (a, b) = (module(cx, "A"), module(cx, "B"));
+            let env = inst_env(cx, a);
+            let e = param(cx, b, "E");
+            assert!(cx.param_env_data(env).is_defaulted(e));
+            let env = cx
+                .param_env(ParamEnvSource::Defparam {
+                    base: env,
+                    env: ParamEnv::EMPTY,
+                    target: e,
+                    value: named_assign(inst_target(cx, a), 0),
+                })
+                .unwrap();
+            let data = cx.param_env_data(env);
+            assert!(!data.is_defaulted(e));
+            assert_eq!(data.defaulted, vec![]);
+            assert_eq!(
+                cx.constant_int_value_of(e, env).unwrap().clone(),
+                num::BigInt::from(8)
+            );
+        });
+    }
+
+    #[test]
+    fn elaborate_from_top_limits_depth() {
+        with_design(DESIGN, |cx| {
+            let insts = cx.elaborate_from_top(module(cx, "A")).unwrap();
+            assert_eq!(insts.len(), 2);
+            assert_eq!(insts[0], (module(cx, "A"), ParamEnv::EMPTY));
+            let w = param(cx, module(cx, "B"), "W");
+            assert!(cx.elaborate_from_top(w).is_err());
+        });
+        with_design(
+            "
+            module A;
+                B #(.N(0)) b();
+            endmodule
+            module B #(parameter int N = 0);
+                B #(.N(N + 1)) b();
+            endmodule
+            ",
+            |cx| {
+                cx.set_inst_depth_limit(4);
+                assert!(cx.elaborate_from_top(module(cx, "A")).is_err());
+            },
+        );
+    }
+
+    #[test]
+    fn param_env_description_lists_params() {
+        with_design(DESIGN, |cx| {
+            let env = inst_env(cx, module(cx, "A"));
+            let params = cx.param_env_to_string(env);
+            assert_eq!(params, "W=8, D=2, E=3");
+            assert!(cx.describe_param_env(env).ends_with(&params));
+        });
+    }
+
+    #[test]
+    fn top_modules_skips_instantiated_modules() {
+        with_design(
+            "
+            module A;
+                A a();
+            endmodule
+            module B;
+            endmodule
+            interface I;
+                B b();
+            endinterface
+            module C;
+                I i();
+            endmodule
+            ",
+            |cx| {
+                assert_eq!(cx.top_modules(), vec![module(cx, "A"), module(cx, "C")]);
+            },
+        );
+    }
+
+    #[test]
+    fn param_override_applies_by_path() {
+        with_design(DESIGN, |cx| {
+            let (a, b) = (module(cx, "A"), module(cx, "B"));
+            let target = inst_target(cx, a);
+            let env = target_env(cx, target);
+            let name = |n| get_name_table().intern(n, true);
+            let value = named_assign(target, 0);
+            assert!(cx.register_param_override(&[name("A")], value).is_err());
+            cx.register_param_override(&[name("A"), name("b"), name("E")], value)
+                .unwrap();
+
+            // The override only applies to the instance at its path.
+            let e = param(cx, b, "E");
+            let value_of = |env| cx.constant_int_value_of(e, env).unwrap().clone();
+            let other = cx.apply_param_overrides(b, &[name("A"), name("c")], env);
+            assert_eq!(other, Ok(env));
+            let overridden = cx
+                .apply_param_overrides(b, &[name("A"), name("b")], env)
+                .unwrap();
+            assert_eq!(value_of(env), num::BigInt::from(3));
+            assert_eq!(value_of(overridden), num::BigInt::from(8));
+        });
+    }
+
+    #[test]
+    fn bindings_sorted_by_param() {
+        with_design(
+            "
+            module A;
+                B #(.E(5), .W(8)) b();
+            endmodule
+            module B #(parameter int W = 1, parameter int D = W / 2, parameter int E = 3);
+            endmodule
+            ",
+            |cx| {
+                let (a, b) = (module(cx, "A"), module(cx, "B"));
+                let env = inst_env(cx, a);
+                let ids: Vec<_> = cx.param_env_data(env).values().map(|(id, _)| id).collect();
+                assert_eq!(
+                    ids,
+                    vec![param(cx, b, "W"), param(cx, b, "D"), param(cx, b, "E")]
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn module_params_resolved_folds_params() {
+        with_design(TYPED, |cx| {
+            let (a, b) = (module(cx, "A"), module(cx, "B"));
+            let resolved = cx.module_params_resolved(b, inst_env(cx, a)).unwrap();
+            let names: Vec<_> = resolved.iter().map(|(name, _)| name.to_string()).collect();
+            assert_eq!(names, vec!["T", "W", "U"]);
+            match resolved[0].1 {
+                ResolvedParamValue::Type(ty) => assert_eq!(ty.to_string(), "logic [3:0]"),
+                ref x => panic!("`T` should resolve to a type, but is {}", x),
+            }
+            match resolved[1].1 {
+                ResolvedParamValue::Value(value) => assert_eq!(value.to_string(), "2"),
+                ref x => panic!("`W` should resolve to a value, but is {}", x),
+            }
+
+            // Parameters that are not assigned fall back to their default.
+            let resolved = cx.module_params_resolved(b, ParamEnv::EMPTY).unwrap();
+            let params: Vec<_> = resolved
+                .iter()
+                .map(|(_, value)| match value {
+                    ResolvedParamValue::Type(ty) => ty.resolve_full().to_string(),
+                    value => value.to_string(),
+                })
+                .collect();
+            assert_eq!(params, vec!["bit", "1", "bit"]);
+        });
+    }
+
+    #[test]
+    fn resolve_type_param_follows_bindings() {
+        with_design(TYPED, |cx| {
+            let (a, b) = (module(cx, "A"), module(cx, "B"));
+            let env = inst_env(cx, a);
+            let (t, w, u) = (param(cx, b, "T"), param(cx, b, "W"), param(cx, b, "U"));
+            let resolve = |param, env| {
+                cx.resolve_type_param(param, env)
+                    .map(|ty| ty.resolve_full().to_string())
+            };
+            assert_eq!(resolve(t, env), Ok("logic [3:0]".to_string()));
+            assert_eq!(resolve(u, env), Ok("logic [3:0]".to_string()));
+            assert_eq!(resolve(t, ParamEnv::EMPTY), Ok("bit".to_string()));
+            assert_eq!(resolve(u, ParamEnv::EMPTY), Ok("bit".to_string()));
+            assert!(cx.resolve_type_param(w, env).is_err());
+        });
+    }
+
+    #[test]
+    fn default_mangler_names_params() {
+        with_design(DESIGN, |cx| {
+            let (a, b) = (module(cx, "A"), module(cx, "B"));
+            let mangler = DefaultParamEnvMangler;
+            assert_eq!(mangler.mangle(cx, b, ParamEnv::EMPTY), "B");
+            assert_eq!(mangler.mangle(cx, b, inst_env(cx, a)), "B__W8__D2__E3");
+        });
+        with_design(TYPED, |cx| {
+            let (a, b) = (module(cx, "A"), module(cx, "B"));
+            let name = DefaultParamEnvMangler.mangle(cx, b, inst_env(cx, a));
+            assert!(name.starts_with("B__Tlogic_3_0___W2__U"), "{}", name);
+        });
+    }
+
+    #[test]
+    fn instances_share_param_env() {
+        let input = "
+            module A;
+                B b1();
+                B #(.W(8)) b2();
+                B b3();
+            endmodule
+            module B #(parameter int W = 1);
+            endmodule
+        ";
+        with_design(input, |cx| {
+            let targets = inst_targets(cx, module(cx, "A"));
+            let ids: Vec<_> = targets.iter().map(|target| target.ast.id()).collect();
+            let envs: Vec<_> = targets
+                .iter()
+                .map(|target| target_env(cx, target))
+                .collect();
+            // The instantiations without overrides share one environment.
+            assert_eq!(envs[0], envs[2]);
+            assert_eq!(cx.instances_for_param_env(envs[0]), vec![ids[0], ids[2]]);
+            assert_eq!(cx.instances_for_param_env(envs[1]), vec![ids[1]]);
+            assert_eq!(cx.instances_for_param_env(ParamEnv::EMPTY), vec![]);
+        });
+    }
+
+    #[test]
+    fn param_override_target_finds_param() {
+        with_design(DESIGN, |cx| {
+            let (a, b) = (module(cx, "A"), module(cx, "B"));
+            let target = inst_target(cx, a);
+            let (w, d) = (target.named_params[0].1.span, target.named_params[1].1.span);
+            assert_eq!(cx.param_override_target(w), None);
+            inst_env(cx, a);
+            assert_eq!(cx.param_override_target(w), Some(param(cx, b, "W")));
+            assert_eq!(cx.param_override_target(d), Some(param(cx, b, "D")));
+            assert_eq!(cx.param_override_target(target.named_params[0].0), None);
+        });
+    }
+
+    #[test]
+    fn param_envs_equivalent_folds_values() {
+        let input = "
+            module A;
+                B #(8) b1();
+                B #(4+4) b2();
+                B #(4) b3();
+                B b4();
+                B #(1) b5();
+                C #(8) c();
+            endmodule
+            module B #(parameter int W = 1);
+            endmodule
+            module C #(parameter int W = 1);
+            endmodule
+        ";
+        with_design(input, |cx| {
+            let envs: Vec<_> = inst_targets(cx, module(cx, "A"))
+                .iter()
+                .map(|target| target_env(cx, target))
+                .collect();
+            assert_ne!(envs[0], envs[1]);
+            assert_eq!(cx.param_envs_equivalent(envs[0], envs[1]), Ok(true));
+            assert_eq!(cx.param_envs_equivalent(envs[0], envs[2]), Ok(false));
+            // An unassigned parameter is compared against its default.
+            assert_eq!(cx.param_envs_equivalent(envs[3], envs[4]), Ok(true));
+            // Environments of different modules are never equivalent.
+            assert_eq!(cx.param_envs_equivalent(envs[0], envs[5]), Ok(false));
+        });
+    }
+
+    #[test]
+    fn remap_param_env_moves_bindings() {
+        let input = "
+            module A;
+                B #(.W(8)) b();
+            endmodule
+            module B #(parameter int W = 1, parameter int D = 2);
+            endmodule
+            module C #(parameter int D = 5, parameter int W = 1);
+            endmodule
+            module E #(parameter int W = 1);
+            endmodule
+        ";
+        with_design(input, |cx| {
+            let (a, c) = (module(cx, "A"), module(cx, "C"));
+            let env = inst_env(cx, a);
+            let remapped = cx.remap_param_env(env, c).unwrap();
+            assert_eq!(env_module(cx, remapped), Ok(c));
+            let value = |name| {
+                cx.constant_int_value_of(param(cx, c, name), remapped)
+                    .unwrap()
+                    .clone()
+            };
+            assert_eq!(value("W"), num::BigInt::from(8));
+            assert_eq!(value("D"), num::BigInt::from(5));
+
+            // `E` has no counterpart for `D`.
+            assert!(cx.remap_param_env(env, module(cx, "E")).is_err());
+        });
+    }
+
+    #[test]
+    fn param_binding_ast_finds_source() {
+        with_design(DESIGN, |cx| {
+            let (a, b) = (module(cx, "A"), module(cx, "B"));
+            let env = inst_env(cx, a);
+            let source = |env, name| {
+                cx.param_binding_ast(env, param(cx, b, name))
+                    .unwrap()
+                    .map(|ast| ast.get_any().unwrap().span().extract())
+            };
+            assert_eq!(source(env, "W"), Some("8".to_string()));
+            assert_eq!(source(env, "E"), Some("3".to_string()));
+            assert_eq!(source(ParamEnv::EMPTY, "W"), None);
+        });
+    }
 }
@@ -1128,6 +1128,8 @@ pub(crate) fn resolve_local<'a>(
     at: ScopeLocation<'a>,
     skip_imports: bool,
 ) -> Result<Option<&'a Def<'a>>> {
+    #[cfg(feature = "trace")]
+    let _span = tracing::trace_span!("resolve_local", name = %name).entered();
     debug!("Resolving `{}` locally at {:?}", name, at);
     let scope = cx.generated_scope(at.scope);
     let mut next = Some(scope);
@@ -677,6 +677,7 @@ pub enum Item<'a> {
     Procedure(#[forward] Procedure<'a>),
     SubroutineDecl(#[forward] SubroutineDecl<'a>),
     ContAssign(#[forward] ContAssign<'a>),
+    Defparam(#[forward] Defparam<'a>),
     GenvarDecl(Vec<GenvarDecl<'a>>),
     GenerateRegion(Span, Vec<Item<'a>>),
     GenerateFor(#[forward] GenerateFor<'a>),
@@ -2199,6 +2200,33 @@ pub struct ParamTypeDecl<'a> {
     #[name]
     pub name: Spanned<Name>,
     pub ty: Option<Type<'a>>,
+    /// The kind of type the parameter is restricted to, as in `parameter type
+    /// enum T`.
+    pub restriction: Option<Spanned<TypeRestriction>>,
+}
+
+/// A restriction of the types a type parameter accepts.
+///
+/// See IEEE 1800-2023 section 6.20.3.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Hash)]
+pub enum TypeRestriction {
+    /// An `enum` type.
+    Enum,
+    /// A `struct` type.
+    Struct,
+    /// A `union` type.
+    Union,
+}
+
+impl std::fmt::Display for TypeRestriction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Enum => write!(f, "enum"),
+            Self::Struct => write!(f, "struct"),
+            Self::Union => write!(f, "union"),
+        }
+    }
 }
 
 /// A single value assignment within a parameter or localparam declaration.
@@ -2233,6 +2261,18 @@ pub struct ContAssign<'a> {
     pub assignments: Vec<(Expr<'a>, Expr<'a>)>,
 }
 
+/// A parameter override statement.
+///
+/// ```text
+/// "defparam" list_of_defparam_assignments ";"
+/// ```
+#[moore_derive::node]
+#[indefinite("defparam statement")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Defparam<'a> {
+    pub assignments: Vec<(Expr<'a>, Expr<'a>)>,
+}
+
 /// A `for` generate statement.
 #[moore_derive::node]
 #[indefinite("for-generate statement")]
@@ -2278,7 +2318,10 @@ pub struct GenerateBlock<'a> {
 pub struct ParamAssignment<'a> {
     pub span: Span,
     pub name: Option<Identifier>,
-    pub expr: TypeOrExpr<'a>,
+    /// The assigned type or expression. `None` if the assignment is left
+    /// empty, as in `#(8, , 16)` or `.W()`, in which case the parameter keeps
+    /// its default.
+    pub expr: Option<TypeOrExpr<'a>>,
 }
 
 /// A port connection in an instantiation.
@@ -782,6 +782,25 @@ fn parse_interface_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<In
     result
 }
 
+/// Parse the optional restriction of a type parameter.
+///
+/// ```text
+/// type_restriction ::= "enum" | "struct" | "union"
+/// ```
+fn parse_type_restriction<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> Option<Spanned<ast::TypeRestriction>> {
+    let (tkn, span) = p.peek(0);
+    let restriction = match tkn {
+        Keyword(Kw::Enum) => ast::TypeRestriction::Enum,
+        Keyword(Kw::Struct) => ast::TypeRestriction::Struct,
+        Keyword(Kw::Union) => ast::TypeRestriction::Union,
+        _ => return None,
+    };
+    p.bump();
+    Some(Spanned::new(restriction, span))
+}
+
 fn parse_parameter_port_list<'n>(
     p: &mut dyn AbstractParser<'n>,
 ) -> ReportedResult<Vec<ParamDecl<'n>>> {
@@ -807,6 +826,7 @@ fn parse_parameter_port_list<'n>(
             // If the next token is the `type` keyword, this is a type parameter.
             // Otherwise this is a value parameter.
             let kind = if p.try_eat(Keyword(Kw::Type)) {
+                let restriction = parse_type_restriction(p);
                 let mut span = p.peek(0).1;
                 let name = parse_identifier_name(p, "parameter name")?;
                 let ty = if p.try_eat(Operator(Op::Assign)) {
@@ -818,7 +838,11 @@ fn parse_parameter_port_list<'n>(
                 span.expand(p.last_span());
                 ast::ParamKind::Type(vec![ast::ParamTypeDecl::new(
                     span,
-                    ParamTypeDeclData { name, ty },
+                    ParamTypeDeclData {
+                        name,
+                        ty,
+                        restriction,
+                    },
                 )])
             } else {
                 // Use a parallel parser to distinguish between the explicit and
@@ -1080,6 +1104,11 @@ fn parse_item_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ItemDat
             return parse_continuous_assign(p).map(|x| ItemData::ContAssign(x));
         }
 
+        // Parameter override
+        Keyword(Kw::Defparam) => {
+            return parse_defparam(p).map(|x| ItemData::Defparam(x));
+        }
+
         // Genvar declaration
         Keyword(Kw::Genvar) => {
             p.bump();
@@ -3111,36 +3140,62 @@ fn parse_implicit_port<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ast
 fn parse_parameter_assignments<'n>(
     p: &mut dyn AbstractParser<'n>,
 ) -> ReportedResult<Vec<ast::ParamAssignment<'n>>> {
-    flanked(p, Paren, |p| {
+    let assigns = flanked(p, Paren, |p| {
         comma_list(
             p,
             CloseDelim(Paren),
             "parameter assignment",
             parse_parameter_assignment,
         )
-    })
+    })?;
+    Ok(assigns.into_iter().flatten().collect())
 }
 
+/// Parse a single parameter assignment.
+///
+/// Returns `None` for a `.*` wildcard, which is only allowed in port
+/// connections. The wildcard is reported and skipped, such that it does not
+/// cause any follow-up errors.
 fn parse_parameter_assignment<'n>(
     p: &mut dyn AbstractParser<'n>,
-) -> ReportedResult<ast::ParamAssignment<'n>> {
+) -> ReportedResult<Option<ast::ParamAssignment<'n>>> {
     let mut span = p.peek(0).1;
     let terms = [Comma, CloseDelim(Paren)];
+    if p.peek(0).0 == Period && p.peek(1).0 == Operator(Op::Mul) {
+        p.bump();
+        p.bump();
+        span.expand(p.last_span());
+        p.add_diag(
+            DiagBuilder2::error("`.*` is not allowed in parameter overrides")
+                .span(span)
+                .add_note("Wildcard connections are only allowed for ports"),
+        );
+        return Ok(None);
+    }
     // If the parameter assignment starts with a ".", this is a named
-    // assignment. Otherwise it's an ordered assignment.
+    // assignment. Otherwise it's an ordered assignment. Either may be left
+    // empty to keep the default of the parameter.
     let (name, expr) = if p.try_eat(Period) {
         let name = parse_identifier(p, "parameter name")?;
-        let expr = flanked(p, Paren, |p| parse_type_or_expr(p, &terms))?;
+        let expr = flanked(p, Paren, |p| {
+            if p.peek(0).0 == CloseDelim(Paren) {
+                Ok(None)
+            } else {
+                parse_type_or_expr(p, &terms).map(Some)
+            }
+        })?;
         (Some(name), expr)
+    } else if terms.contains(&p.peek(0).0) {
+        (None, None)
     } else {
-        (None, parse_type_or_expr(p, &terms)?)
+        (None, Some(parse_type_or_expr(p, &terms)?))
     };
     span.expand(p.last_span());
-    Ok(ast::ParamAssignment {
+    Ok(Some(ast::ParamAssignment {
         span: span,
         name: name,
         expr: expr,
-    })
+    }))
 }
 
 fn parse_procedure<'n>(
@@ -3811,6 +3866,15 @@ fn parse_continuous_assign<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult
     ))
 }
 
+fn parse_defparam<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Defparam<'n>> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Defparam))?;
+    let assignments = comma_list_nonempty(p, Semicolon, "defparam assignment", parse_assignment)?;
+    p.require_reported(Semicolon)?;
+    span.expand(p.last_span());
+    Ok(ast::Defparam::new(span, ast::DefparamData { assignments }))
+}
+
 fn parse_if_or_case<'n>(
     p: &mut dyn AbstractParser<'n>,
     up: Option<UniquePriority>,
@@ -6045,6 +6109,7 @@ fn parse_param_decl<'n>(
     // If the next token is the `type` keyword, this is a type parameter.
     // Otherwise this is a value parameter.
     let kind = if p.try_eat(Keyword(Kw::Type)) {
+        let restriction = parse_type_restriction(p);
         let decls = comma_list_nonempty(p, predicate, "parameter name", |p| {
             let mut span = p.peek(0).1;
             let name = parse_identifier_name(p, "parameter name")?;
@@ -6057,7 +6122,11 @@ fn parse_param_decl<'n>(
             span.expand(p.last_span());
             Ok(ast::ParamTypeDecl::new(
                 span,
-                ast::ParamTypeDeclData { name, ty },
+                ast::ParamTypeDeclData {
+                    name,
+                    ty,
+                    restriction,
+                },
             ))
         })?;
         p.anticipate(&[Semicolon, Comma, CloseDelim(Paren)])?;
@@ -1465,6 +1465,15 @@ impl<'a> UnpackedType<'a> {
         self.dims.is_empty() && self.resolve_full().core == UnpackedCore::String
     }
 
+    /// Check if this type is a real, like `real` or `shortreal`.
+    pub fn is_real(&self) -> bool {
+        self.dims.is_empty()
+            && match self.resolve_full().core {
+                UnpackedCore::Real(_) => true,
+                _ => false,
+            }
+    }
+
     /// Check if this type will coalesce to a scalar type in LLHD, like `i42`.
     pub fn coalesces_to_llhd_scalar(&self) -> bool {
         self.get_packed()
@@ -30,9 +30,11 @@
 
 use crate::crate_prelude::*;
 use crate::{
+    ast_map::AstNode,
     call_mapping::CallArgSource,
     common::arenas::Alloc,
     hir::HirNode,
+    param_env::report_open_param,
     port_list,
     resolver::{DefNode, InstTarget},
     syntax::ast::BasicNode,
@@ -337,7 +339,19 @@ pub(crate) fn type_of_value_param<'a>(
             return cx.type_of_expr(Ref(hir), assigned_id.env());
         }
         Some(ParamEnvBinding::Direct(t)) => return t.ty,
-        _ => (),
+        Some(ParamEnvBinding::Default(default_id)) => {
+            let hir = match cx.hir_of(default_id) {
+                Ok(HirNode::Expr(e)) => e,
+                Err(()) => return UnpackedType::make_error(),
+                _ => unreachable!(),
+            };
+            return cx.type_of_expr(Ref(hir), env);
+        }
+        None if env_data.is_open(ast.id()) => {
+            report_open_param(cx, ast.id(), env);
+            return UnpackedType::make_error();
+        }
+        None => (),
     }
 
     // Otherwise try to infer the type from the default expression.
@@ -364,6 +378,60 @@ pub(crate) fn type_of_value_param<'a>(
     UnpackedType::make_error()
 }
 
+/// Determine the type of a value parameter in a parameter environment.
+///
+/// This is the parameter's explicit type if it has one, or the type of the
+/// expression it is assigned in `env` otherwise.
+#[moore_derive::query]
+pub(crate) fn param_type<'a>(
+    cx: &impl Context<'a>,
+    param_id: NodeId,
+    env: ParamEnv,
+) -> Result<&'a UnpackedType<'a>> {
+    match cx.ast_of(param_id)? {
+        AstNode::ValueParam(_, decl) => Ok(cx.type_of_value_param(Ref(decl), env)),
+        _ => {
+            cx.emit(
+                DiagBuilder2::bug(format!(
+                    "{} is not a value parameter",
+                    cx.ast_for_id(param_id)
+                ))
+                .span(cx.span(param_id)),
+            );
+            Err(())
+        }
+    }
+}
+
+/// Resolve a type parameter to the type it is bound to in an environment.
+///
+/// This is the type assigned to the parameter in `env`, or the parameter's
+/// default type otherwise. Fails if the parameter does not resolve to a
+/// valid type, which has been reported already.
+#[moore_derive::query]
+pub(crate) fn resolve_type_param<'a>(
+    cx: &impl Context<'a>,
+    param_id: NodeId,
+    env: ParamEnv,
+) -> Result<&'a UnpackedType<'a>> {
+    match cx.ast_of(param_id)? {
+        AstNode::TypeParam(_, decl) => match cx.map_to_type(Ref(decl as &dyn ast::AnyNode), env) {
+            Some(ty) if !ty.is_error() => Ok(ty),
+            _ => Err(()),
+        },
+        _ => {
+            cx.emit(
+                DiagBuilder2::bug(format!(
+                    "{} is not a type parameter",
+                    cx.ast_for_id(param_id)
+                ))
+                .span(cx.span(param_id)),
+            );
+            Err(())
+        }
+    }
+}
+
 /// Determine the type of an instance.
 #[moore_derive::query]
 pub(crate) fn type_of_inst<'a>(
@@ -451,11 +519,12 @@ pub(crate) fn map_to_type<'a>(
                 Ok(x) => x,
                 _ => return Some(UnpackedType::make_error()),
             };
-            let env = match cx.param_env(ParamEnvSource::InterfaceInst {
-                interface: Ref(hir),
+            // An interface used as a type, e.g. in a port, receives its
+            // parameters from the instance connected to it. Leave them open.
+            let env = match cx.param_env(ParamEnvSource::Partial {
+                module: hir.ast.id(),
                 env,
-                pos: &[],
-                named: &[],
+                fixed: &[],
             }) {
                 Ok(x) => x,
                 _ => return Some(UnpackedType::make_error()),
@@ -511,7 +580,14 @@ pub(crate) fn map_to_type<'a>(
                     }
                 }
                 Some(ParamEnvBinding::Direct(t)) => return Some(t),
-                _ => (),
+                Some(ParamEnvBinding::Default(default_id)) => {
+                    return cx.map_to_type(Ref(cx.ast_for_id(default_id)), env)
+                }
+                None if env_data.is_open(ast.id()) => {
+                    report_open_param(cx, ast.id(), env);
+                    return Some(UnpackedType::make_error());
+                }
+                None => (),
             }
 
             // Look for a default assignment.
@@ -1076,6 +1152,7 @@ pub(crate) fn type_of_expr<'a>(
         hir::ExprKind::IntConst { .. }
         | hir::ExprKind::TimeConst(..)
         | hir::ExprKind::StringConst(..)
+        | hir::ExprKind::Unbounded
         | hir::ExprKind::Ident(..)
         | hir::ExprKind::Scope(..)
         | hir::ExprKind::Concat(..)
@@ -1090,6 +1167,7 @@ pub(crate) fn type_of_expr<'a>(
         | hir::ExprKind::Builtin(hir::BuiltinCall::OneHot(_))
         | hir::ExprKind::Builtin(hir::BuiltinCall::OneHot0(_))
         | hir::ExprKind::Builtin(hir::BuiltinCall::IsUnknown(_))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::IsUnbounded(_))
         | hir::ExprKind::Builtin(hir::BuiltinCall::ArrayDim(..))
         | hir::ExprKind::Field(..)
         | hir::ExprKind::Index(..)
@@ -1598,6 +1676,12 @@ fn self_determined_expr_type<'gcx>(
         // Time constants are of time type.
         hir::ExprKind::TimeConst(_) => Some(UnpackedType::make_time()),
 
+        // The unbounded marker may only be assigned to integer parameters, so
+        // it is treated as an `int`.
+        hir::ExprKind::Unbounded => {
+            Some(PackedType::make(cx, ty::IntAtomType::Int).to_unpacked(cx))
+        }
+
         // String literals behave like a packed array containing the characters.
         hir::ExprKind::StringConst(string) => Some(
             ty::PackedType::make_dims(
@@ -1749,7 +1833,8 @@ fn self_determined_expr_type<'gcx>(
         // These builtin functions evaluate to the bit type.
         hir::ExprKind::Builtin(hir::BuiltinCall::OneHot(_))
         | hir::ExprKind::Builtin(hir::BuiltinCall::OneHot0(_))
-        | hir::ExprKind::Builtin(hir::BuiltinCall::IsUnknown(_)) => {
+        | hir::ExprKind::Builtin(hir::BuiltinCall::IsUnknown(_))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::IsUnbounded(_)) => {
             Some(PackedType::make(cx, ty::IntVecType::Bit).to_unpacked(cx))
         }
 
@@ -2329,6 +2414,8 @@ pub(crate) fn type_context<'a>(
             let pattern = cx.hir_of_expr(Ref(pattern)).ok()?;
             return type_context_imposed_by_expr(cx, onto.id(), pattern, env);
         }
+        // The value of a defparam is self-determined.
+        ast::AllNode::Defparam(..) => return None,
         _ => (),
     }
 
@@ -2823,6 +2910,27 @@ pub(crate) fn expr_is_lvalue<'gcx>(cx: &impl Context<'gcx>, onto: NodeId, _env:
     }
 }
 
+/// Point at the assignment that supplied a parameter value, if `expr` refers
+/// to an overridden parameter.
+fn note_param_assignment<'a>(
+    cx: &impl Context<'a>,
+    expr: NodeId,
+    env: ParamEnv,
+    diag: DiagBuilder2,
+) -> DiagBuilder2 {
+    let param = match cx.hir_of(expr) {
+        Ok(HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::Ident(_),
+            ..
+        })) => cx.resolve_node(expr, env).ok(),
+        _ => None,
+    };
+    match param.and_then(|param| cx.param_assignment_span(env, param)) {
+        Some(span) => diag.add_note("Parameter value assigned here:").span(span),
+        None => diag,
+    }
+}
+
 fn size_from_bounds_expr<'a>(
     cx: &impl Context<'a>,
     expr: NodeId,
@@ -2832,6 +2940,16 @@ fn size_from_bounds_expr<'a>(
     let size = match cx.constant_value_of(expr, env).kind {
         ValueKind::Int(ref int, ..) => int,
         ValueKind::Error => return Err(()),
+        ValueKind::Unbounded => {
+            let d = DiagBuilder2::error(format!(
+                "array bound `{}` is unbounded",
+                cx.span(expr).extract()
+            ))
+            .span(cx.span(expr))
+            .add_note("Use a queue `[$]` to declare an array without an upper bound");
+            cx.emit(note_param_assignment(cx, expr, env, d));
+            return Err(());
+        }
         _ => {
             let span = cx.span(expr);
             cx.emit(
@@ -2869,6 +2987,14 @@ fn range_from_bounds_exprs<'a>(
         match cx.constant_value_of(bound, env).kind {
             ValueKind::Int(ref int, ..) => Ok(int),
             ValueKind::Error => Err(()),
+            ValueKind::Unbounded => {
+                let span = cx.span(bound);
+                let d =
+                    DiagBuilder2::error(format!("array bound `{}` is unbounded", span.extract()))
+                        .span(span);
+                cx.emit(note_param_assignment(cx, bound, env, d));
+                Err(())
+            }
             _ => {
                 let span = cx.span(bound);
                 cx.emit(
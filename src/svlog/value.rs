@@ -14,6 +14,7 @@
 use crate::{
     crate_prelude::*,
     hir::HirNode,
+    param_env::report_open_param,
     ty::{SbvType, UnpackedType},
     ParamEnv, ParamEnvBinding,
 };
@@ -52,10 +53,16 @@ impl<'t> ValueData<'t> {
             ValueKind::Time(ref v) => v.is_zero(),
             ValueKind::StructOrArray(_) => false,
             ValueKind::String(ref v) => v.is_empty(),
+            ValueKind::Unbounded => false,
             ValueKind::Error => true,
         }
     }
 
+    /// Check if this value is the unbounded marker `$`.
+    pub fn is_unbounded(&self) -> bool {
+        self.kind == ValueKind::Unbounded
+    }
+
     /// Convert the value to an integer.
     pub fn get_int(&self) -> Option<&BigInt> {
         match self.kind {
@@ -91,6 +98,12 @@ pub enum ValueKind<'t> {
     /// is due to `String` guaranteeing that the encoded byte sequence is valid
     /// UTF8, which SystemVerilog does not guarantee.
     String(Vec<u8>),
+    /// The unbounded marker `$`.
+    ///
+    /// This may be assigned to integer parameters to indicate that they have no
+    /// upper bound, and can be detected with `$isunbounded`. It does not have
+    /// an integer value and cannot participate in any operation.
+    Unbounded,
     /// An error occurred during value computation.
     Error,
 }
@@ -125,6 +138,7 @@ impl std::fmt::Display for ValueKind<'_> {
                 }
                 write!(f, "\"")
             }
+            ValueKind::Unbounded => write!(f, "$"),
             ValueKind::Error => write!(f, "<error>"),
         }
     }
@@ -205,6 +219,14 @@ pub fn make_string<'a>(ty: &'a UnpackedType<'a>, bytes: Vec<u8>) -> ValueData<'a
     }
 }
 
+/// Create a new unbounded value `$`.
+pub fn make_unbounded<'a>(ty: &'a UnpackedType<'a>) -> ValueData<'a> {
+    ValueData {
+        ty,
+        kind: ValueKind::Unbounded,
+    }
+}
+
 /// Determine the constant value of a node.
 #[moore_derive::query]
 pub(crate) fn constant_value_of<'a>(
@@ -239,8 +261,15 @@ fn constant_value_of_inner<'a>(cx: &impl Context<'a>, node_id: NodeId, env: Para
                     return cx.constant_value_of(assigned_id.id(), assigned_id.env())
                 }
                 Some(ParamEnvBinding::Direct(v)) => return v,
+                Some(ParamEnvBinding::Default(default_id)) => {
+                    return cx.constant_value_of(default_id, env)
+                }
                 _ => (),
             }
+            if env_data.is_open(node_id) {
+                report_open_param(cx, node_id, env);
+                return cx.intern_value(make_error(UnpackedType::make_error()));
+            }
             if let Some(default) = param.default {
                 return cx.constant_value_of(default, env);
             }
@@ -311,6 +340,16 @@ pub(crate) fn const_mir_rvalue_int<'a>(
     match cx.const_mir_rvalue(mir).kind {
         ValueKind::Int(ref x, ..) => Ok(x),
         ValueKind::Error => Err(()),
+        ValueKind::Unbounded => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "`{}` is unbounded and has no integer value",
+                    mir.span.extract()
+                ))
+                .span(mir.span),
+            );
+            Err(())
+        }
         _ => {
             cx.emit(
                 DiagBuilder2::error(format!(
@@ -433,7 +472,7 @@ fn const_mir_rvalue_inner<'a>(cx: &impl Context<'a>, mir: &'a mir::Rvalue<'a>) -
 
         mir::RvalueKind::UnaryBitwise { op, arg } => {
             let arg_val = cx.const_mir_rvalue(arg.into());
-            if arg_val.is_error() {
+            if arg_val.is_error() || reject_unbounded(cx, &[(arg, arg_val)]) {
                 return cx.intern_value(make_error(mir.ty));
             }
             match arg_val.kind {
@@ -453,7 +492,10 @@ fn const_mir_rvalue_inner<'a>(cx: &impl Context<'a>, mir: &'a mir::Rvalue<'a>) -
         mir::RvalueKind::BinaryBitwise { op, lhs, rhs } => {
             let lhs_val = cx.const_mir_rvalue(lhs.into());
             let rhs_val = cx.const_mir_rvalue(rhs.into());
-            if lhs_val.is_error() || rhs_val.is_error() {
+            if lhs_val.is_error()
+                || rhs_val.is_error()
+                || reject_unbounded(cx, &[(lhs, lhs_val), (rhs, rhs_val)])
+            {
                 return cx.intern_value(make_error(mir.ty));
             }
             match (&lhs_val.kind, &rhs_val.kind) {
@@ -475,7 +517,7 @@ fn const_mir_rvalue_inner<'a>(cx: &impl Context<'a>, mir: &'a mir::Rvalue<'a>) -
 
         mir::RvalueKind::IntUnaryArith { op, arg, .. } => {
             let arg_val = cx.const_mir_rvalue(arg.into());
-            if arg_val.is_error() {
+            if arg_val.is_error() || reject_unbounded(cx, &[(arg, arg_val)]) {
                 return cx.intern_value(make_error(mir.ty));
             }
             match arg_val.kind {
@@ -490,7 +532,10 @@ fn const_mir_rvalue_inner<'a>(cx: &impl Context<'a>, mir: &'a mir::Rvalue<'a>) -
         mir::RvalueKind::IntBinaryArith { op, lhs, rhs, .. } => {
             let lhs_val = cx.const_mir_rvalue(lhs.into());
             let rhs_val = cx.const_mir_rvalue(rhs.into());
-            if lhs_val.is_error() || rhs_val.is_error() {
+            if lhs_val.is_error()
+                || rhs_val.is_error()
+                || reject_unbounded(cx, &[(lhs, lhs_val), (rhs, rhs_val)])
+            {
                 return cx.intern_value(make_error(mir.ty));
             }
             match (&lhs_val.kind, &rhs_val.kind) {
@@ -513,7 +558,10 @@ fn const_mir_rvalue_inner<'a>(cx: &impl Context<'a>, mir: &'a mir::Rvalue<'a>) -
         mir::RvalueKind::IntComp { op, lhs, rhs, .. } => {
             let lhs_val = cx.const_mir_rvalue(lhs.into());
             let rhs_val = cx.const_mir_rvalue(rhs.into());
-            if lhs_val.is_error() || rhs_val.is_error() {
+            if lhs_val.is_error()
+                || rhs_val.is_error()
+                || reject_unbounded(cx, &[(lhs, lhs_val), (rhs, rhs_val)])
+            {
                 return cx.intern_value(make_error(mir.ty));
             }
             match (&lhs_val.kind, &rhs_val.kind) {
@@ -547,7 +595,7 @@ fn const_mir_rvalue_inner<'a>(cx: &impl Context<'a>, mir: &'a mir::Rvalue<'a>) -
 
         mir::RvalueKind::Repeat(count, value) => {
             let value_const = cx.const_mir_rvalue(value.into());
-            if value_const.is_error() {
+            if value_const.is_error() || reject_unbounded(cx, &[(value, value_const)]) {
                 return cx.intern_value(make_error(mir.ty));
             }
             let sbvt = value.ty.simple_bit_vector(cx, value.span);
@@ -603,7 +651,10 @@ fn const_mir_rvalue_inner<'a>(cx: &impl Context<'a>, mir: &'a mir::Rvalue<'a>) -
         } => {
             let value_val = cx.const_mir_rvalue(value.into());
             let amount_val = cx.const_mir_rvalue(amount.into());
-            if value_val.is_error() || amount_val.is_error() {
+            if value_val.is_error()
+                || amount_val.is_error()
+                || reject_unbounded(cx, &[(value, value_val), (amount, amount_val)])
+            {
                 return cx.intern_value(make_error(mir.ty));
             }
             match (&value_val.kind, &amount_val.kind) {
@@ -626,7 +677,7 @@ fn const_mir_rvalue_inner<'a>(cx: &impl Context<'a>, mir: &'a mir::Rvalue<'a>) -
 
         mir::RvalueKind::Reduction { op, arg } => {
             let arg_val = cx.const_mir_rvalue(arg.into());
-            if arg_val.is_error() {
+            if arg_val.is_error() || reject_unbounded(cx, &[(arg, arg_val)]) {
                 return cx.intern_value(make_error(mir.ty));
             }
             match arg_val.kind {
@@ -645,7 +696,7 @@ fn const_mir_rvalue_inner<'a>(cx: &impl Context<'a>, mir: &'a mir::Rvalue<'a>) -
             ..
         } => {
             let inner_val = cx.const_mir_rvalue(value.into());
-            if inner_val.is_error() {
+            if inner_val.is_error() || reject_unbounded(cx, &[(value, inner_val)]) {
                 return cx.intern_value(make_error(mir.ty));
             }
             let base = match cx.const_mir_rvalue_int(Ref(base)) {
@@ -755,6 +806,30 @@ fn const_mir_rvalue_inner<'a>(cx: &impl Context<'a>, mir: &'a mir::Rvalue<'a>) -
     }
 }
 
+/// Report unbounded values used as operands of an operation.
+///
+/// Returns `true` if any of the operands is the unbounded marker `$`.
+fn reject_unbounded<'a>(
+    cx: &impl Context<'a>,
+    operands: &[(&'a mir::Rvalue<'a>, Value<'a>)],
+) -> bool {
+    let mut rejected = false;
+    for &(mir, value) in operands {
+        if value.is_unbounded() {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "unbounded value `{}` cannot be used in an expression",
+                    mir.span.extract()
+                ))
+                .span(mir.span)
+                .add_note("`$` may only be assigned to parameters and tested with `$isunbounded`"),
+            );
+            rejected = true;
+        }
+    }
+    rejected
+}
+
 fn const_unary_bitwise_int<'gcx>(
     _cx: &impl Context<'gcx>,
     ty: SbvType,
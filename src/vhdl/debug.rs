@@ -50,6 +50,16 @@ pub fn emit_pkgs(sess: &Session, nodes: Vec<&ast::DesignUnit>) {
     };
     lib.accept(&mut v);
 
+    // Dump the scopes of the library and its packages.
+    if sess.opts.dump_scopes {
+        debugln!("dumping scopes");
+        lib.accept(&mut ScopeDumpVisitor);
+    }
+
+    // Report use clauses that did not contribute to name resolution.
+    debugln!("checking for unused imports");
+    lib.warn_unused_imports(sess);
+
     // // Visit the names.
     // debugln!("names:");
     // let mut v = NameVisitor;
@@ -72,6 +82,26 @@ impl<'t> Visitor<'t> for IdentityVisitor {
     }
 }
 
+struct ScopeDumpVisitor;
+
+impl<'t> Visitor<'t> for ScopeDumpVisitor {
+    fn as_visitor(&mut self) -> &mut Visitor<'t> {
+        self
+    }
+
+    fn visit_library(&mut self, hir: &'t Library<'t>) {
+        println!("library {}:", hir.name());
+        hir.scope().dump(&mut std::io::stdout().lock(), 1).unwrap();
+        hir.walk(self);
+    }
+
+    fn visit_pkg(&mut self, hir: &'t hir::Package2<'t>) {
+        println!("package {}:", hir.name().value);
+        hir.scope().dump(&mut std::io::stdout().lock(), 1).unwrap();
+        hir.walk(self);
+    }
+}
+
 // struct NameVisitor;
 
 // impl<'t> Visitor<'t> for NameVisitor {
@@ -0,0 +1,155 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Alias declarations
+
+use crate::hir::prelude::*;
+use crate::scope2::ambiguity_error;
+
+/// An alias declaration.
+///
+/// An alias introduces an additional name for an existing declaration. Name
+/// resolution transparently replaces the alias with the declarations it
+/// denotes, such that overload resolution sees the profiles of the actual
+/// subprograms or enumeration literals.
+///
+/// See IEEE 1076-2008 section 6.6.
+#[derive(Debug)]
+pub struct AliasDecl2<'t> {
+    span: Span,
+    name: Spanned<ResolvableName>,
+    targets: Vec<Spanned<Def2<'t>>>,
+}
+
+impl<'t> AliasDecl2<'t> {
+    /// Return the declarations the alias denotes.
+    ///
+    /// This is a single declaration, or the overloads of a subprogram or
+    /// enumeration literal.
+    pub fn targets(&self) -> &[Spanned<Def2<'t>>] {
+        &self.targets
+    }
+}
+
+impl<'t> FromAst<'t> for AliasDecl2<'t> {
+    type AllocInput = &'t ast::AliasDecl;
+    type LatentInput = Self::AllocInput;
+    type Context = AllocContext<'t>;
+    type Latent = &'t AliasDecl2<'t>;
+
+    fn alloc_slot(ast: Self::AllocInput, context: Self::Context) -> Result<Self::Latent> {
+        // The aliased name is resolved right away rather than on demand, since
+        // it has to be visible at the point of the alias declaration. This also
+        // keeps the alias from referring to itself.
+        let alias = context.alloc(Self::from_ast(ast, context)?);
+        context.define(alias.name, Def2::Alias(alias))?;
+        Ok(alias)
+    }
+
+    fn from_ast(ast: Self::LatentInput, context: Self::Context) -> Result<Self> {
+        let name = ResolvableName::from_primary_name(&ast.name, context)?;
+        let primary = ResolvableName::from_primary_name(&ast.target.primary, context)?;
+        let mut path = vec![primary];
+        for part in &ast.target.parts {
+            match *part {
+                ast::NamePart::Select(ref primary) => {
+                    path.push(ResolvableName::from_primary_name(primary, context)?)
+                }
+                _ => {
+                    context.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` cannot be aliased",
+                            ast.target.span.extract()
+                        ))
+                        .span(ast.target.span)
+                        .add_note("Only simple and selected names are supported as alias targets"),
+                    );
+                    return Err(());
+                }
+            }
+        }
+
+        // Resolve the prefix of a selected name to the library or package the
+        // last segment is looked up in. The last segment may resolve to
+        // multiple overloads.
+        let (last, prefix) = path.split_last().unwrap();
+        let (targets, scope) = if prefix.is_empty() {
+            (context.resolve(last.value, true), context.scope())
+        } else {
+            let scope = match context.scope().resolve_selected(prefix, context.sess)? {
+                Spanned {
+                    value: Def2::Lib(x),
+                    ..
+                } => x.scope(),
+                Spanned {
+                    value: Def2::Pkg(x),
+                    ..
+                } => x.poll()?.scope(),
+                Spanned {
+                    value: Def2::PkgInst(x),
+                    ..
+                } => x.scope(),
+                def => {
+                    let prev = prefix[prefix.len() - 1];
+                    context.emit(
+                        DiagBuilder2::error(format!(
+                            "cannot select `{}` from `{}`",
+                            last.value, prev.value
+                        ))
+                        .span(last.span)
+                        .add_note(format!(
+                            "`{}` is a {}, not a library or package",
+                            prev.value,
+                            def.value.desc_kind()
+                        ))
+                        .span(prev.span),
+                    );
+                    return Err(());
+                }
+            };
+            (scope.resolve(last.value, false), scope)
+        };
+        if targets.is_empty() {
+            let mut d = DiagBuilder2::error(format!("`{}` is unknown", last.value)).span(last.span);
+            if let Some(similar) = scope.suggest(last.value) {
+                d = d.add_note(format!("Did you mean `{}`?", similar));
+            }
+            context.emit(d);
+            return Err(());
+        }
+        if targets.len() > 1 && !targets.iter().all(|t| t.value.is_overloadable()) {
+            context.emit(ambiguity_error(*last, &targets));
+            return Err(());
+        }
+        Ok(AliasDecl2 {
+            span: ast.span,
+            name,
+            targets,
+        })
+    }
+}
+
+impl<'t> Node<'t> for AliasDecl2<'t> {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn desc_kind(&self) -> String {
+        "alias".into()
+    }
+
+    fn desc_name(&self) -> String {
+        format!("alias `{}`", self.name.value)
+    }
+
+    fn accept(&'t self, visitor: &mut Visitor<'t>) {
+        visitor.visit_alias_decl(self);
+    }
+
+    fn walk(&'t self, _visitor: &mut Visitor<'t>) {}
+}
+
+impl<'t> Decl2<'t> for AliasDecl2<'t> {
+    fn name(&self) -> Spanned<ResolvableName> {
+        self.name
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::common::errors::*;
 use crate::common::score::Result;
-use crate::common::source::Spanned;
+use crate::common::source::{Span, Spanned};
 use crate::common::{SessionContext, Verbosity};
 
 use crate::arenas::Alloc;
@@ -48,16 +48,34 @@ impl<'t> ScopeContext<'t> for AllocContext<'t> {
     }
 
     fn import_def(&self, name: ResolvableName, def: Spanned<Def2<'t>>) -> Result<()> {
-        self.scope.import_def(name, def)
+        self.scope.import_def(name, def, self.sess)
     }
 
-    fn import_scope(&self, scope: &'t ScopeData<'t>) -> Result<()> {
-        self.scope.import_scope(scope)
+    fn import_scope(&self, scope: &'t ScopeData<'t>, span: Span) -> Result<()> {
+        self.scope.import_scope(scope, span, self.sess)
+    }
+
+    fn import_scope_selective(
+        &self,
+        scope: &'t ScopeData<'t>,
+        names: &[ResolvableName],
+        span: Span,
+    ) -> Result<()> {
+        self.scope
+            .import_scope_selective(scope, names, span, self.sess)
     }
 
     fn resolve(&self, name: ResolvableName, recur: bool) -> Vec<Spanned<Def2<'t>>> {
         self.scope.resolve(name, recur)
     }
+
+    fn resolve_with_scope(
+        &self,
+        name: ResolvableName,
+        recur: bool,
+    ) -> Vec<(Spanned<Def2<'t>>, &'t ScopeData<'t>)> {
+        self.scope.resolve_with_scope(name, recur)
+    }
 }
 
 impl<'t> DiagEmitter for AllocContext<'t> {
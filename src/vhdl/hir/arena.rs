@@ -12,14 +12,18 @@ make_arenas!(
 
         library: Library<'t>,
         package: Package2<'t>,
+        package_inst: PackageInst2<'t>,
         type_decl: TypeDecl2<'t>,
         subtype_ind: SubtypeInd2<'t>,
         const_decl: ConstDecl<'t>,
+        subprog_decl: SubprogDecl2<'t>,
+        alias_decl: AliasDecl2<'t>,
         lit_expr: LitExpr,
 
         package_slot: Slot<'t, Package2<'t>>,
         type_decl_slot: Slot<'t, TypeDecl2<'t>>,
         subtype_ind_slot: Slot<'t, SubtypeInd2<'t>>,
         const_decl_slot: Slot<'t, ConstDecl<'t>>,
+        subprog_decl_slot: Slot<'t, SubprogDecl2<'t>>,
     }
 );
@@ -0,0 +1,234 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Generic environments of package instantiations
+
+use crate::common::SessionContext;
+use crate::hir::prelude::*;
+use std::collections::HashMap;
+
+/// A formal generic declared in the generic clause of a package.
+#[derive(Copy, Clone, Debug)]
+pub struct FormalGeneric<'t> {
+    /// The name of the generic.
+    pub name: Spanned<Name>,
+    /// The interface declaration that declares the generic.
+    pub decl: &'t ast::IntfDecl,
+    /// Whether the declaration provides a default for the generic.
+    pub has_default: bool,
+}
+
+/// The actual bound to a formal generic.
+#[derive(Copy, Clone, Debug)]
+pub enum GenericBinding<'t> {
+    /// The generic is bound to the actual of an association element.
+    Actual(&'t ast::ParenElem),
+    /// The generic is bound to the default provided by its declaration.
+    Default,
+}
+
+/// A generic environment generated by a package instantiation.
+///
+/// This is the VHDL counterpart to the parameter environment of SystemVerilog
+/// instantiations. It binds every formal generic of the instantiated package,
+/// in declaration order.
+#[derive(Debug)]
+pub struct GenericEnv<'t> {
+    bindings: Vec<(FormalGeneric<'t>, GenericBinding<'t>)>,
+}
+
+impl<'t> GenericEnv<'t> {
+    /// Find the binding of a generic.
+    pub fn find(&self, name: Name) -> Option<GenericBinding<'t>> {
+        self.bindings
+            .iter()
+            .find(|(formal, _)| formal.name.value == name)
+            .map(|&(_, binding)| binding)
+    }
+
+    /// Return the bindings of all generics, in declaration order.
+    pub fn bindings(&self) -> &[(FormalGeneric<'t>, GenericBinding<'t>)] {
+        &self.bindings
+    }
+}
+
+/// Collect the formal generics of a package, in declaration order.
+pub fn package_generics(ast: &ast::PkgDecl) -> Vec<FormalGeneric> {
+    let mut formals = vec![];
+    for decl in &ast.decls {
+        let decls = match *decl {
+            ast::DeclItem::PortgenClause(
+                _,
+                Spanned {
+                    value: ast::PortgenKind::Generic,
+                    ..
+                },
+                ref decls,
+            ) => decls,
+            _ => continue,
+        };
+        for decl in &decls.value {
+            match *decl {
+                ast::IntfDecl::TypeDecl(ref d) => formals.push(FormalGeneric {
+                    name: d.name,
+                    decl,
+                    has_default: false,
+                }),
+                ast::IntfDecl::SubprogSpec(ref d) => {
+                    if let ast::PrimaryNameKind::Ident(name) = d.spec.name.kind {
+                        formals.push(FormalGeneric {
+                            name: Spanned::new(name, d.spec.name.span),
+                            decl,
+                            has_default: d.default.is_some(),
+                        })
+                    }
+                }
+                ast::IntfDecl::PkgInst(ref d) => formals.push(FormalGeneric {
+                    name: d.name,
+                    decl,
+                    has_default: false,
+                }),
+                ast::IntfDecl::ObjDecl(ref d) => {
+                    formals.extend(d.names.iter().map(|n| FormalGeneric {
+                        name: Spanned::new(n.name, n.span),
+                        decl,
+                        has_default: d.default.is_some(),
+                    }))
+                }
+            }
+        }
+    }
+    formals
+}
+
+/// Associate the actuals of a generic map with the formal generics.
+///
+/// Positional associations are matched with the formals in declaration order,
+/// and must precede all named associations. Every association is checked
+/// before bailing out, such that all unknown or duplicate generics are
+/// reported at once. Formals without an actual fall back to their default.
+///
+/// See IEEE 1076-2008 section 6.5.7.
+pub fn compute_generic_env<'t>(
+    formals: &[FormalGeneric<'t>],
+    actuals: &'t ast::ParenElems,
+    ctx: &impl SessionContext,
+) -> Result<GenericEnv<'t>> {
+    let mut name_table = HashMap::new();
+    for (index, formal) in formals.iter().enumerate() {
+        name_table.entry(formal.name.value).or_insert(index);
+    }
+
+    // Associate the actuals with the formals.
+    let mut failed = false;
+    let mut assigns = vec![];
+    let mut first_named: Option<Span> = None;
+    for (index, elem) in actuals.value.iter().enumerate() {
+        if elem.choices.value.is_empty() {
+            if let Some(named) = first_named {
+                ctx.emit(
+                    DiagBuilder2::error("positional associations must appear before named ones")
+                        .span(elem.span)
+                        .add_note("First named association was here:")
+                        .span(named),
+                );
+                failed = true;
+            }
+            if index < formals.len() {
+                assigns.push((index, elem));
+            } else {
+                ctx.emit(
+                    DiagBuilder2::error(format!("superfluous generic association {}", index + 1))
+                        .span(elem.span)
+                        .add_note(format!("package only has {} generic(s)", formals.len())),
+                );
+                failed = true;
+            }
+            continue;
+        }
+        first_named.get_or_insert(elem.span);
+        let name = match formal_name(elem) {
+            Some(name) => name,
+            None => {
+                ctx.emit(
+                    DiagBuilder2::error("expected the name of a generic").span(elem.choices.span),
+                );
+                failed = true;
+                continue;
+            }
+        };
+        match name_table.get(&name.value) {
+            Some(&index) => assigns.push((index, elem)),
+            None => {
+                ctx.emit(
+                    DiagBuilder2::error(format!("no generic `{}` in package", name.value))
+                        .span(name.span),
+                );
+                failed = true;
+            }
+        }
+    }
+
+    // Make sure no generic is associated more than once.
+    let mut bindings: Vec<Option<&'t ast::ParenElem>> = vec![None; formals.len()];
+    for (index, elem) in assigns {
+        match bindings[index] {
+            Some(previous) => {
+                ctx.emit(
+                    DiagBuilder2::error(format!(
+                        "generic `{}` associated multiple times",
+                        formals[index].name.value
+                    ))
+                    .span(elem.span)
+                    .add_note("Previous association was here:")
+                    .span(previous.span),
+                );
+                failed = true;
+            }
+            None => bindings[index] = Some(elem),
+        }
+    }
+
+    // Fall back to the defaults of the remaining generics.
+    for (formal, binding) in formals.iter().zip(bindings.iter()) {
+        if binding.is_none() && !formal.has_default {
+            ctx.emit(
+                DiagBuilder2::error(format!(
+                    "generic `{}` not associated and has no default",
+                    formal.name.value
+                ))
+                .span(actuals.span)
+                .add_note(format!("`{}` declared here:", formal.name.value))
+                .span(formal.name.span),
+            );
+            failed = true;
+        }
+    }
+    if failed {
+        return Err(());
+    }
+
+    Ok(GenericEnv {
+        bindings: formals
+            .iter()
+            .cloned()
+            .zip(bindings.into_iter().map(|b| match b {
+                Some(elem) => GenericBinding::Actual(elem),
+                None => GenericBinding::Default,
+            }))
+            .collect(),
+    })
+}
+
+/// Extract the name of the formal from a named association element.
+fn formal_name(elem: &ast::ParenElem) -> Option<Spanned<Name>> {
+    if elem.choices.value.len() != 1 {
+        return None;
+    }
+    match elem.choices.value[0].data {
+        ast::ExprData::NameExpr(ref name) if name.parts.is_empty() => match name.primary.kind {
+            ast::PrimaryNameKind::Ident(n) => Some(Spanned::new(n, name.primary.span)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
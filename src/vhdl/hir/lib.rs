@@ -7,6 +7,7 @@ use crate::hir::prelude::*;
 use crate::common::name::{get_name_table, Name};
 use crate::common::score::Result;
 use crate::common::source::{Span, Spanned, INVALID_SPAN};
+use crate::common::SessionContext;
 
 use crate::arenas::Alloc;
 use crate::hir::visit::Visitor;
@@ -67,6 +68,37 @@ impl<'t> Library<'t> {
     pub fn scope(&self) -> &'t ScopeData<'t> {
         self.scope
     }
+
+    /// Warn about the use clauses in the library that were never used.
+    ///
+    /// This concludes the lowering of the library and should be called once
+    /// all names in its design units have been resolved. The scope of the
+    /// library and the scopes of all packages in it are checked, including
+    /// nested packages.
+    pub fn warn_unused_imports(&'t self, ctx: &'t SessionContext) {
+        self.accept(&mut UnusedImportVisitor { ctx });
+    }
+}
+
+/// Warns about the unused imports of every scope it visits.
+struct UnusedImportVisitor<'a> {
+    ctx: &'a SessionContext,
+}
+
+impl<'a, 't> Visitor<'t> for UnusedImportVisitor<'a> {
+    fn as_visitor(&mut self) -> &mut Visitor<'t> {
+        self
+    }
+
+    fn visit_library(&mut self, hir: &'t Library<'t>) {
+        hir.walk(self);
+        hir.scope().warn_unused_imports(self.ctx);
+    }
+
+    fn visit_pkg(&mut self, hir: &'t Package2<'t>) {
+        hir.walk(self);
+        hir.scope().warn_unused_imports(self.ctx);
+    }
 }
 
 impl<'t> Node<'t> for Library<'t> {
@@ -3,6 +3,7 @@
 use std;
 
 use crate::hir::prelude::*;
+use crate::scope2::ambiguity_error;
 
 pub fn apply_use_clauses<'a, I>(clauses: I, context: AllocContext)
 where
@@ -21,8 +22,13 @@ fn apply_use_clause(clause: &ast::CompoundName, context: AllocContext) -> Result
     let pn = ResolvableName::from_primary_name(&clause.primary, context)?;
     let mut lookup = context.resolve(pn.value, true);
     let mut lookup_name = pn;
+    let mut lookup_scope = None;
     if lookup.is_empty() {
-        context.emit(DiagBuilder2::error(format!("`{}` is unknown", pn.value)).span(pn.span));
+        context.emit(suggest(
+            DiagBuilder2::error(format!("`{}` is unknown", pn.value)).span(pn.span),
+            context.scope(),
+            pn.value,
+        ));
         return Err(());
     }
     // debugln!("`{}` resolved to {:?}", pn.value, lookup);
@@ -31,19 +37,14 @@ fn apply_use_clause(clause: &ast::CompoundName, context: AllocContext) -> Result
     for part in &clause.parts {
         // Ensure the name is unique.
         if lookup.len() > 1 {
-            let mut d = DiagBuilder2::error(format!("`{}` is ambiguous", lookup_name.value))
-                .span(lookup_name.span)
-                .add_note("Refers to the following:");
-            for l in lookup {
-                d = d.span(l.span);
-            }
-            context.emit(d);
+            context.emit(ambiguity_error(lookup_name, &lookup));
             return Err(());
         }
         let def = lookup.into_iter().next().unwrap();
         let scope = match def.value {
             Def2::Lib(x) => x.scope(),
             Def2::Pkg(x) => x.poll()?.scope(),
+            Def2::PkgInst(x) => x.scope(),
             _ => {
                 context.emit(
                     DiagBuilder2::error(format!("cannot select into {}", def.value.desc_kind()))
@@ -58,17 +59,20 @@ fn apply_use_clause(clause: &ast::CompoundName, context: AllocContext) -> Result
             ast::NamePart::Select(ref primary) => {
                 lookup_name = ResolvableName::from_primary_name(primary, context)?;
                 lookup = scope.resolve(lookup_name.value, false);
+                lookup_scope = Some(scope);
                 // debugln!("`{}` resolved to {:?}", lookup_name.value, lookup);
                 if lookup.is_empty() {
-                    context.emit(
+                    context.emit(suggest(
                         DiagBuilder2::error(format!("`{}` is unknown", lookup_name.value))
                             .span(lookup_name.span),
-                    );
+                        scope,
+                        lookup_name.value,
+                    ));
                     return Err(());
                 }
             }
             ast::NamePart::SelectAll(..) => {
-                context.import_scope(scope)?;
+                context.import_scope(scope, clause.span)?;
                 return Ok(());
             }
             _ => {
@@ -82,8 +86,23 @@ fn apply_use_clause(clause: &ast::CompoundName, context: AllocContext) -> Result
     }
 
     debugln!("`{}` resolved to {:?}", clause.span.extract(), lookup);
-    for l in lookup {
-        context.import_def(lookup_name.value, l)?;
+    match lookup_scope {
+        // A name selected from a scope is imported by name, such that the
+        // lookup is repeated in that scope whenever the name is resolved.
+        Some(scope) => context.import_scope_selective(scope, &[lookup_name.value], clause.span)?,
+        None => {
+            for l in lookup {
+                context.import_def(lookup_name.value, l)?;
+            }
+        }
     }
     Ok(())
 }
+
+/// Add a note suggesting a similar name to a diagnostic, if there is one.
+fn suggest(diag: DiagBuilder2, scope: &ScopeData, name: ResolvableName) -> DiagBuilder2 {
+    match scope.suggest(name) {
+        Some(similar) => diag.add_note(format!("Did you mean `{}`?", similar)),
+        None => diag,
+    }
+}
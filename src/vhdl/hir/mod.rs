@@ -13,9 +13,11 @@ use crate::op::*;
 use crate::score::*;
 pub use crate::syntax::ast::Dir;
 
+mod alias_decl;
 mod alloc_ctx;
 mod arena;
 mod expr;
+mod generic_env;
 mod lib;
 mod misc;
 mod node;
@@ -23,19 +25,23 @@ mod obj_decl;
 mod pkg;
 mod prelude;
 mod slot;
+mod subprog_decl;
 mod subtype_decl;
 mod type_decl;
 pub mod visit;
 
+pub use self::alias_decl::*;
 pub use self::alloc_ctx::*;
 pub use self::arena::*;
 pub use self::expr::*;
+pub use self::generic_env::*;
 pub use self::lib::*;
 pub use self::misc::*;
 pub use self::node::*;
 pub use self::obj_decl::*;
 pub use self::pkg::*;
 pub use self::slot::*;
+pub use self::subprog_decl::*;
 pub use self::subtype_decl::*;
 pub use self::type_decl::*;
 
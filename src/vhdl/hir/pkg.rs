@@ -4,13 +4,15 @@
 
 use crate::hir::apply_use_clauses;
 use crate::hir::prelude::*;
-use crate::hir::TypeDecl2;
+use crate::hir::{compute_generic_env, package_generics, FormalGeneric, GenericEnv};
+use crate::hir::{AliasDecl2, SubprogDecl2, TypeDecl2};
 
 #[derive(Debug)]
 pub struct Package2<'t> {
     id: NodeId,
     span: Span,
     name: Spanned<Name>,
+    generics: Vec<FormalGeneric<'t>>,
     decls: Vec<&'t LatentNode<'t, Decl2<'t>>>,
     scope: &'t ScopeData<'t>,
 }
@@ -21,6 +23,11 @@ impl<'t> Package2<'t> {
         self.name
     }
 
+    /// Return the formal generics of this package.
+    pub fn generics(&self) -> &[FormalGeneric<'t>] {
+        &self.generics
+    }
+
     /// Return the declarations made in this package.
     pub fn decls(&self) -> &[&'t LatentNode<'t, Decl2<'t>>] {
         &self.decls
@@ -47,7 +54,6 @@ impl<'t> FromAst<'t> for Package2<'t> {
     fn from_ast(ast: Self::LatentInput, context: Self::Context) -> Result<Self> {
         debugln!("create package decl {}", ast.name.value);
         let context = context.create_subscope();
-        let mut uses = Vec::new();
         let decls = ast
             .decls
             .iter()
@@ -59,19 +65,38 @@ impl<'t> FromAst<'t> for Package2<'t> {
                     ast::DeclItem::TypeDecl(ref decl) => {
                         Some(TypeDecl2::alloc_slot(decl, context).ok()?)
                     }
+                    ast::DeclItem::SubprogDecl(ref decl) => {
+                        Some(SubprogDecl2::alloc_slot(decl, context).ok()?)
+                    }
+                    // Aliases are resolved when they are declared, and only
+                    // live on as definitions in the scope.
+                    ast::DeclItem::AliasDecl(ref decl) => {
+                        AliasDecl2::alloc_slot(decl, context).ok();
+                        None
+                    }
+                    // Package instantiations are resolved when they are
+                    // declared, like aliases, and live on as definitions in
+                    // the scope.
+                    ast::DeclItem::PkgInst(ref decl) => {
+                        PackageInst2::alloc_slot(decl, context).ok();
+                        None
+                    }
+                    // Use clauses take effect right away, such that the
+                    // declarations following them can refer to the names they
+                    // make visible.
                     ast::DeclItem::UseClause(_, ref clause) => {
-                        uses.extend(clause.value.iter());
+                        apply_use_clauses(clause.value.iter(), context);
                         None
                     }
                     _ => None,
                 }
             })
             .collect::<Vec<_>>();
-        apply_use_clauses(uses.into_iter(), context);
         Ok(Package2 {
             id: NodeId::alloc(),
             span: ast.span,
             name: ast.name,
+            generics: package_generics(ast),
             decls: decls,
             scope: context.scope(),
         })
@@ -108,3 +133,143 @@ impl<'t> Decl2<'t> for Package2<'t> {
         self.name.map(Into::into)
     }
 }
+
+/// A package instantiation.
+///
+/// See IEEE 1076-2008 section 4.9.
+#[derive(Debug)]
+pub struct PackageInst2<'t> {
+    span: Span,
+    name: Spanned<Name>,
+    package: &'t Package2<'t>,
+    generic_env: Option<GenericEnv<'t>>,
+}
+
+impl<'t> PackageInst2<'t> {
+    /// Return the name of this package instance.
+    pub fn name(&self) -> Spanned<Name> {
+        self.name
+    }
+
+    /// Return the instantiated package.
+    pub fn package(&self) -> &'t Package2<'t> {
+        self.package
+    }
+
+    /// Return the generic environment of the instantiation.
+    ///
+    /// This is `None` if the instantiation has no generic map.
+    pub fn generic_env(&self) -> Option<&GenericEnv<'t>> {
+        self.generic_env.as_ref()
+    }
+
+    /// Return the scope holding the declarations of the instance.
+    ///
+    /// Note that this is the scope of the instantiated package, such that the
+    /// declarations resolve without the generics being substituted.
+    pub fn scope(&self) -> &'t ScopeData<'t> {
+        self.package.scope()
+    }
+}
+
+impl<'t> FromAst<'t> for PackageInst2<'t> {
+    type AllocInput = &'t ast::PkgInst;
+    type LatentInput = Self::AllocInput;
+    type Context = AllocContext<'t>;
+    type Latent = &'t PackageInst2<'t>;
+
+    fn alloc_slot(ast: Self::AllocInput, context: Self::Context) -> Result<Self::Latent> {
+        // The instantiated package is resolved right away, such that the
+        // instance is available to the declarations following it.
+        let inst = context.alloc(Self::from_ast(ast, context)?);
+        context.define(ast.name.map(Into::into), Def2::PkgInst(inst))?;
+        Ok(inst)
+    }
+
+    fn from_ast(ast: Self::LatentInput, context: Self::Context) -> Result<Self> {
+        let package = resolve_inst_target(ast, context)?;
+        let generic_env = match ast.generics {
+            Some(ref generics) => {
+                Some(compute_generic_env(package.generics(), generics, &context)?)
+            }
+            None => None,
+        };
+        Ok(PackageInst2 {
+            span: ast.span,
+            name: ast.name,
+            package,
+            generic_env,
+        })
+    }
+}
+
+impl<'t> Node<'t> for PackageInst2<'t> {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn desc_kind(&self) -> String {
+        "package instance".into()
+    }
+
+    fn desc_name(&self) -> String {
+        format!("package instance `{}`", self.name.value)
+    }
+
+    fn accept(&'t self, visitor: &mut Visitor<'t>) {
+        visitor.visit_pkg_inst(self);
+    }
+
+    fn walk(&'t self, visitor: &mut Visitor<'t>) {
+        visitor.visit_name(self.name);
+    }
+}
+
+impl<'t> Decl2<'t> for PackageInst2<'t> {
+    fn name(&self) -> Spanned<ResolvableName> {
+        self.name.map(Into::into)
+    }
+}
+
+/// Resolve the package instantiated by a package instantiation.
+fn resolve_inst_target<'t>(
+    ast: &'t ast::PkgInst,
+    context: AllocContext<'t>,
+) -> Result<&'t Package2<'t>> {
+    let primary = ResolvableName::from_primary_name(&ast.target.primary, context)?;
+    let mut path = vec![primary];
+    for part in &ast.target.parts {
+        match *part {
+            ast::NamePart::Select(ref primary) => {
+                path.push(ResolvableName::from_primary_name(primary, context)?)
+            }
+            _ => {
+                context.emit(
+                    DiagBuilder2::error(format!(
+                        "`{}` is not a package name",
+                        ast.target.span.extract()
+                    ))
+                    .span(ast.target.span),
+                );
+                return Err(());
+            }
+        }
+    }
+    match context.scope().resolve_selected(&path, context.sess)? {
+        Spanned {
+            value: Def2::Pkg(x),
+            ..
+        } => x.poll(),
+        def => {
+            context.emit(
+                DiagBuilder2::error(format!(
+                    "`{}` is a {}, not a package",
+                    ast.target.span.extract(),
+                    def.value.desc_kind()
+                ))
+                .span(ast.target.span),
+            );
+            Err(())
+        }
+    }
+}
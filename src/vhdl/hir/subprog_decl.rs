@@ -0,0 +1,140 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Subprogram declarations
+
+use crate::hir::prelude::*;
+
+/// A subprogram declaration.
+///
+/// See IEEE 1076-2008 section 4.2.
+#[derive(Debug)]
+pub struct SubprogDecl2<'t> {
+    span: Span,
+    name: Spanned<ResolvableName>,
+    kind: ast::SubprogKind,
+    profile: SubprogProfile,
+    marker: &'t (),
+}
+
+/// The parameter and result type profile of a subprogram.
+///
+/// Two subprograms of the same name are homographs if their profiles are the
+/// same. See IEEE 1076-2008 section 4.5.1. The profile records the type marks
+/// as they appear in the source, since they have not been resolved at the
+/// time the subprogram is declared. Every type mark is recorded as the names
+/// it selects, e.g. `[work, p, T]` for `work.p.T`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubprogProfile {
+    /// The type marks of the parameters.
+    pub params: Vec<Vec<ResolvableName>>,
+    /// The type mark of the return type, if this is a function.
+    pub result: Option<Vec<ResolvableName>>,
+}
+
+impl<'t> SubprogDecl2<'t> {
+    /// Return the kind of subprogram, i.e. whether this is a procedure or a
+    /// function.
+    pub fn kind(&self) -> ast::SubprogKind {
+        self.kind
+    }
+
+    /// Return the parameter and result type profile of the subprogram.
+    pub fn profile(&self) -> &SubprogProfile {
+        &self.profile
+    }
+}
+
+impl<'t> FromAst<'t> for SubprogDecl2<'t> {
+    type AllocInput = &'t ast::Subprog;
+    type LatentInput = Self::AllocInput;
+    type Context = AllocContext<'t>;
+    type Latent = &'t Slot<'t, Self>;
+
+    fn alloc_slot(ast: Self::AllocInput, context: Self::Context) -> Result<Self::Latent> {
+        let slot = context.alloc(Slot::new(ast, context));
+        let name = ResolvableName::from_primary_name(&ast.spec.name, context)?;
+        context.define(name, Def2::Subprogram(slot))?;
+        Ok(slot)
+    }
+
+    fn from_ast(ast: Self::LatentInput, context: Self::Context) -> Result<Self> {
+        let mut params = vec![];
+        for decl in ast.spec.params.iter().flat_map(|p| p.iter()) {
+            if let ast::IntfDecl::ObjDecl(ref decl) = *decl {
+                let ty = type_mark(&decl.ty.name, context)?;
+                params.extend(decl.names.iter().map(|_| ty.clone()));
+            }
+        }
+        let result = match ast.spec.retty {
+            Some(ref retty) => Some(type_mark(retty, context)?),
+            None => None,
+        };
+        Ok(SubprogDecl2 {
+            span: ast.span,
+            name: ResolvableName::from_primary_name(&ast.spec.name, context)?,
+            kind: ast.spec.kind,
+            profile: SubprogProfile { params, result },
+            marker: &(),
+        })
+    }
+}
+
+/// Collect the names selected by a type mark.
+///
+/// A trailing constraint such as the `(7 downto 0)` in `bit_vector(7 downto 0)`
+/// does not contribute to the profile and is skipped.
+fn type_mark(name: &ast::CompoundName, context: AllocContext) -> Result<Vec<ResolvableName>> {
+    let mut path = vec![ResolvableName::from_primary_name(&name.primary, context)?.value];
+    for part in &name.parts {
+        match *part {
+            ast::NamePart::Select(ref primary) => {
+                path.push(ResolvableName::from_primary_name(primary, context)?.value)
+            }
+            ast::NamePart::Call(..) | ast::NamePart::Range(..) => break,
+            _ => {
+                context.emit(
+                    DiagBuilder2::error(format!(
+                        "`{}` is not a valid type mark",
+                        name.span.extract()
+                    ))
+                    .span(name.span)
+                    .add_note("Only simple and selected names are supported as type marks"),
+                );
+                return Err(());
+            }
+        }
+    }
+    Ok(path)
+}
+
+impl<'t> Node<'t> for SubprogDecl2<'t> {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn desc_kind(&self) -> String {
+        match self.kind {
+            ast::SubprogKind::Proc => "procedure declaration".into(),
+            ast::SubprogKind::Func => "function declaration".into(),
+        }
+    }
+
+    fn desc_name(&self) -> String {
+        match self.kind {
+            ast::SubprogKind::Proc => format!("procedure `{}`", self.name.value),
+            ast::SubprogKind::Func => format!("function `{}`", self.name.value),
+        }
+    }
+
+    fn accept(&'t self, visitor: &mut Visitor<'t>) {
+        visitor.visit_subprog_decl(self);
+    }
+
+    fn walk(&'t self, _visitor: &mut Visitor<'t>) {}
+}
+
+impl<'t> Decl2<'t> for SubprogDecl2<'t> {
+    fn name(&self) -> Spanned<ResolvableName> {
+        self.name
+    }
+}
@@ -19,6 +19,10 @@ pub trait Visitor<'t> {
         hir.walk(self.as_visitor());
     }
 
+    fn visit_pkg_inst(&mut self, hir: &'t PackageInst2<'t>) {
+        hir.walk(self.as_visitor());
+    }
+
     fn visit_type_decl(&mut self, hir: &'t TypeDecl2<'t>) {
         hir.walk(self.as_visitor());
     }
@@ -27,5 +31,13 @@ pub trait Visitor<'t> {
         hir.walk(self.as_visitor());
     }
 
+    fn visit_subprog_decl(&mut self, hir: &'t SubprogDecl2<'t>) {
+        hir.walk(self.as_visitor());
+    }
+
+    fn visit_alias_decl(&mut self, hir: &'t AliasDecl2<'t>) {
+        hir.walk(self.as_visitor());
+    }
+
     fn visit_literal_expr(&mut self, _: &'t LitExpr) {}
 }
@@ -6,14 +6,19 @@
 
 #![deny(missing_docs)]
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+#[cfg(not(feature = "parallel"))]
+use std::cell::{Ref, RefMut};
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+#[cfg(feature = "parallel")]
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use crate::common::errors::*;
 use crate::common::score::Result;
-use crate::common::source::Spanned;
+use crate::common::source::{Span, Spanned, INVALID_SPAN};
 use crate::common::{SessionContext, Verbosity};
 
 use crate::hir::{self, Node};
@@ -28,12 +33,21 @@ pub enum Def2<'t> {
     Lib(&'t hir::Library<'t>),
     /// A package.
     Pkg(&'t hir::LatentNode<'t, hir::Package2<'t>>),
+    /// A package instance.
+    PkgInst(&'t hir::PackageInst2<'t>),
     /// A type declaration.
     Type(&'t hir::LatentNode<'t, hir::TypeDecl2<'t>>),
     /// An enumeration type variant.
     Enum(TypeVariantDef<'t>),
     /// A physical type unit.
     Unit(TypeVariantDef<'t>),
+    /// A subprogram.
+    Subprogram(&'t hir::Slot<'t, hir::SubprogDecl2<'t>>),
+    /// An alias of other definitions.
+    ///
+    /// Name resolution replaces aliases with their targets, such that this
+    /// only appears among the definitions made in a scope.
+    Alias(&'t hir::AliasDecl2<'t>),
 }
 
 impl<'t> fmt::Debug for Def2<'t> {
@@ -42,9 +56,12 @@ impl<'t> fmt::Debug for Def2<'t> {
             Def2::Node(x) => write!(f, "Node({:?})", x as *const _),
             Def2::Lib(x) => write!(f, "Lib({:?})", x as *const _),
             Def2::Pkg(x) => write!(f, "Pkg({:?})", x as *const _),
+            Def2::PkgInst(x) => write!(f, "PkgInst({:?})", x as *const _),
             Def2::Type(x) => write!(f, "Type({:?})", x as *const _),
             Def2::Enum(x) => write!(f, "Enum({:?}, {})", x.0 as *const _, x.1),
             Def2::Unit(x) => write!(f, "Unit({:?}, {})", x.0 as *const _, x.1),
+            Def2::Subprogram(x) => write!(f, "Subprogram({:?})", x as *const _),
+            Def2::Alias(x) => write!(f, "Alias({:?})", x as *const _),
         }
     }
 }
@@ -57,22 +74,62 @@ impl<'t> Def2<'t> {
             Def2::Node(x) => x.poll().unwrap().desc_kind(),
             Def2::Lib(x) => x.desc_kind(),
             Def2::Pkg(x) => x.poll().unwrap().desc_kind(),
+            Def2::PkgInst(x) => x.desc_kind(),
             Def2::Type(x) => x.poll().unwrap().desc_kind(),
             Def2::Enum(x) => x.0.poll().unwrap().desc_kind(),
             Def2::Unit(x) => x.0.poll().unwrap().desc_kind(),
+            Def2::Subprogram(x) => x.poll().unwrap().desc_kind(),
+            Def2::Alias(x) => x.desc_kind(),
+        }
+    }
+
+    /// Check whether the definition may be overloaded.
+    ///
+    /// Only definitions with a parameter and result type profile may coexist
+    /// with other definitions of the same name.
+    pub fn is_overloadable(&self) -> bool {
+        match *self {
+            Def2::Enum(..) | Def2::Subprogram(..) => true,
+            _ => false,
+        }
+    }
+
+    /// Check whether two definitions of the same name are homographs.
+    ///
+    /// Overloadable definitions are homographs if they have the same profile.
+    /// For enumeration literals this is the enumeration type they belong to.
+    /// Enumeration literals and subprograms never share a profile, since the
+    /// literals are not declared with a parameter list. All other definitions
+    /// are always homographs of each other.
+    pub fn is_homograph(&self, other: &Self) -> bool {
+        match (*self, *other) {
+            (Def2::Enum(a), Def2::Enum(b)) => same_node(a.0, b.0),
+            (Def2::Subprogram(a), Def2::Subprogram(b)) => match (a.poll(), b.poll()) {
+                (Ok(a), Ok(b)) => a.profile() == b.profile(),
+                _ => false,
+            },
+            (a, b) => !a.is_overloadable() || !b.is_overloadable(),
         }
     }
 }
 
+/// Definitions are compared by identity.
+///
+/// Two definitions are equal if they point at the same HIR node, regardless of
+/// how many handles to that node exist. Enumeration literals and physical units
+/// additionally have to refer to the same variant of their type.
 impl<'t> PartialEq for Def2<'t> {
     fn eq(&self, other: &Self) -> bool {
         match (*self, *other) {
-            (Def2::Node(a), Def2::Node(b)) => (a as *const _ == b as *const _),
-            (Def2::Lib(a), Def2::Lib(b)) => (a as *const _ == b as *const _),
-            (Def2::Pkg(a), Def2::Pkg(b)) => (a as *const _ == b as *const _),
-            (Def2::Type(a), Def2::Type(b)) => (a as *const _ == b as *const _),
+            (Def2::Node(a), Def2::Node(b)) => same_node(a, b),
+            (Def2::Lib(a), Def2::Lib(b)) => same_node(a, b),
+            (Def2::Pkg(a), Def2::Pkg(b)) => same_node(a, b),
+            (Def2::PkgInst(a), Def2::PkgInst(b)) => same_node(a, b),
+            (Def2::Type(a), Def2::Type(b)) => same_node(a, b),
             (Def2::Enum(a), Def2::Enum(b)) => (a == b),
             (Def2::Unit(a), Def2::Unit(b)) => (a == b),
+            (Def2::Subprogram(a), Def2::Subprogram(b)) => same_node(a, b),
+            (Def2::Alias(a), Def2::Alias(b)) => same_node(a, b),
             _ => false,
         }
     }
@@ -80,20 +137,125 @@ impl<'t> PartialEq for Def2<'t> {
 
 impl<'t> Eq for Def2<'t> {}
 
+/// Check whether two references point at the same node.
+///
+/// Only the addresses are compared. The vtables of trait objects such as
+/// `LatentNode` are not guaranteed to be unique, which would make the same
+/// node compare unequal to itself.
+fn same_node<T: ?Sized, U: ?Sized>(a: &T, b: &U) -> bool {
+    a as *const T as *const u8 == b as *const U as *const u8
+}
+
+/// The interior mutability of a scope.
+///
+/// This is a `RefCell` by default. With the `parallel` feature it is a
+/// `RwLock` instead, such that names can be resolved in a scope from multiple
+/// threads concurrently, while definitions and imports take a write lock.
+/// Note that this alone does not make `ScopeData` `Sync`: the definitions
+/// point at HIR nodes which are lowered lazily behind a `RefCell`, and which
+/// are allocated in arenas that are not `Sync` either. The bookkeeping of the
+/// scope itself is thread-safe, see [`ScopeId`].
+pub struct ScopeCell<T> {
+    #[cfg(not(feature = "parallel"))]
+    inner: RefCell<T>,
+    #[cfg(feature = "parallel")]
+    inner: RwLock<T>,
+}
+
+#[cfg(not(feature = "parallel"))]
+impl<T> ScopeCell<T> {
+    /// Create a new cell.
+    pub fn new(value: T) -> ScopeCell<T> {
+        ScopeCell {
+            inner: RefCell::new(value),
+        }
+    }
+
+    /// Borrow the contents for reading.
+    pub fn borrow(&self) -> Ref<T> {
+        self.inner.borrow()
+    }
+
+    /// Borrow the contents for writing.
+    pub fn borrow_mut(&self) -> RefMut<T> {
+        self.inner.borrow_mut()
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<T> ScopeCell<T> {
+    /// Create a new cell.
+    pub fn new(value: T) -> ScopeCell<T> {
+        ScopeCell {
+            inner: RwLock::new(value),
+        }
+    }
+
+    /// Acquire a read lock on the contents.
+    pub fn borrow(&self) -> RwLockReadGuard<T> {
+        self.inner.read().unwrap()
+    }
+
+    /// Acquire a write lock on the contents.
+    pub fn borrow_mut(&self) -> RwLockWriteGuard<T> {
+        self.inner.write().unwrap()
+    }
+}
+
+impl<T: Clone> Clone for ScopeCell<T> {
+    fn clone(&self) -> ScopeCell<T> {
+        ScopeCell::new(self.borrow().clone())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ScopeCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.borrow().fmt(f)
+    }
+}
+
+/// Identifies a scope by its address.
+///
+/// Unlike a raw pointer this is `Send` and `Sync`, such that it can key the
+/// bookkeeping of imports in a scope shared across threads.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ScopeId(usize);
+
+impl ScopeId {
+    /// Get the id of a scope.
+    pub fn of(scope: &ScopeData) -> ScopeId {
+        ScopeId(scope as *const _ as usize)
+    }
+}
+
 /// A scope.
+///
+/// See [`ScopeCell`] for how scopes can be shared across threads.
 #[derive(Clone, Debug)]
 pub struct ScopeData<'t> {
     /// The parent scope.
     pub parent: Option<&'t ScopeData<'t>>,
 
     /// The definitions made in this scope.
-    pub defs: RefCell<HashMap<ResolvableName, Vec<Spanned<Def2<'t>>>>>,
+    pub defs: ScopeCell<HashMap<ResolvableName, Vec<Spanned<Def2<'t>>>>>,
 
     /// The definitions imported from other scopes.
-    pub imported_defs: RefCell<HashMap<ResolvableName, Vec<Spanned<Def2<'t>>>>>,
+    pub imported_defs: ScopeCell<HashMap<ResolvableName, Vec<Spanned<Def2<'t>>>>>,
 
     /// The explicitly imported scopes.
-    pub imported_scopes: RefCell<HashSet<&'t ScopeData<'t>>>,
+    pub imported_scopes: ScopeCell<HashSet<&'t ScopeData<'t>>>,
+
+    /// The scopes from which only a selection of names has been imported.
+    pub selected_scopes: ScopeCell<HashMap<&'t ScopeData<'t>, HashSet<ResolvableName>>>,
+
+    /// The location of the use clause that imported a scope or a name.
+    pub import_spans: ScopeCell<HashMap<(ScopeId, Option<ResolvableName>), Span>>,
+
+    /// The imported scopes and names that have been used to resolve a name.
+    pub used_imports: ScopeCell<HashSet<(ScopeId, Option<ResolvableName>)>>,
+
+    /// The number of modifications made to the scope.
+    pub generation: ScopeCell<u64>,
 }
 
 impl<'t> ScopeData<'t> {
@@ -101,12 +263,32 @@ impl<'t> ScopeData<'t> {
     pub fn root() -> ScopeData<'t> {
         ScopeData {
             parent: None,
-            defs: RefCell::new(HashMap::new()),
-            imported_defs: RefCell::new(HashMap::new()),
-            imported_scopes: RefCell::new(HashSet::new()),
+            defs: ScopeCell::new(HashMap::new()),
+            imported_defs: ScopeCell::new(HashMap::new()),
+            imported_scopes: ScopeCell::new(HashSet::new()),
+            selected_scopes: ScopeCell::new(HashMap::new()),
+            import_spans: ScopeCell::new(HashMap::new()),
+            used_imports: ScopeCell::new(HashSet::new()),
+            generation: ScopeCell::new(0),
         }
     }
 
+    /// Return the generation of the scope.
+    ///
+    /// The generation is bumped whenever a definition is added to or removed
+    /// from the scope, or something is imported into it. Caches can record the
+    /// generation along with a result and discard the result if it changed.
+    /// Note that the generation only covers this scope; resolution may also
+    /// depend on the parent and imported scopes.
+    pub fn generation(&self) -> u64 {
+        *self.generation.borrow()
+    }
+
+    /// Record a modification of the scope.
+    fn bump_generation(&self) {
+        *self.generation.borrow_mut() += 1;
+    }
+
     /// Create a new scope.
     pub fn new(parent: &'t ScopeData<'t>) -> ScopeData<'t> {
         ScopeData {
@@ -128,89 +310,859 @@ impl<'t> ScopeData<'t> {
             );
         }
         debugln!("define `{}` as {:?}", name.value, def);
-        match def {
-            // Handle overloadable cases.
-            Def2::Enum(..) => {
-                self.defs
-                    .borrow_mut()
-                    .entry(name.value)
-                    .or_insert_with(|| Vec::new())
-                    .push(Spanned::new(def, name.span));
-                Ok(())
-            }
+        let mut defs = self.defs.borrow_mut();
+        let entry = defs.entry(name.value).or_insert_with(|| Vec::new());
+        if let Some(existing) = entry.iter().find(|e| e.value.is_homograph(&def)) {
+            ctx.emit(
+                DiagBuilder2::error(format!("`{}` has already been declared", name.value))
+                    .span(name.span)
+                    .add_note("Previous declaration was here:")
+                    .span(existing.span),
+            );
+            return Err(());
+        }
+        entry.push(Spanned::new(def, name.span));
+        self.bump_generation();
+        Ok(())
+    }
 
-            // Handle unique cases.
-            _ => {
-                let ins = self
-                    .defs
-                    .borrow_mut()
-                    .insert(name.value, vec![Spanned::new(def, name.span)]);
-                if let Some(existing) = ins {
-                    ctx.emit(
-                        DiagBuilder2::error(format!("`{}` has already been declared", name.value))
-                            .span(name.span)
-                            .add_note("Previous declaration was here:")
-                            .span(existing.last().unwrap().span),
-                    );
-                    Err(())
-                } else {
-                    Ok(())
-                }
+    /// Remove all definitions of a name from the scope.
+    ///
+    /// This is useful during error recovery, to keep a bad definition from
+    /// causing further errors. Only the definitions made directly in this
+    /// scope are removed, not the imported ones.
+    pub fn undefine(&self, name: ResolvableName) {
+        debugln!("undefine `{}`", name);
+        self.defs.borrow_mut().remove(&name);
+        self.bump_generation();
+    }
+
+    /// Remove the definition of a name made at a specific location.
+    ///
+    /// Other definitions of the same name, e.g. overloads, are kept. Only the
+    /// definitions made directly in this scope are considered.
+    pub fn undefine_one(&self, name: ResolvableName, span: Span) {
+        debugln!("undefine `{}` at {:?}", name, span);
+        let mut defs = self.defs.borrow_mut();
+        if let Some(entry) = defs.get_mut(&name) {
+            entry.retain(|def| def.span != span);
+            if entry.is_empty() {
+                defs.remove(&name);
             }
         }
+        self.bump_generation();
     }
 
     /// Import a definition into the scope.
-    pub fn import_def(&self, name: ResolvableName, def: Spanned<Def2<'t>>) -> Result<()> {
-        self.imported_defs
-            .borrow_mut()
-            .entry(name)
-            .or_insert_with(|| Vec::new())
-            .push(def);
+    ///
+    /// Definitions that have already been imported under the same name are
+    /// ignored.
+    pub fn import_def(
+        &self,
+        name: ResolvableName,
+        def: Spanned<Def2<'t>>,
+        ctx: &SessionContext,
+    ) -> Result<()> {
+        if ctx.has_verbosity(Verbosity::NAMES) {
+            ctx.emit(
+                DiagBuilder2::note(format!("import `{}` as {:?}", name, def.value)).span(def.span),
+            );
+        }
+        debugln!("import `{}` as {:?}", name, def.value);
+        let mut imported_defs = self.imported_defs.borrow_mut();
+        let entry = imported_defs.entry(name).or_insert_with(|| Vec::new());
+        // Importing the same definition multiple times is harmless and must
+        // not make it look ambiguous. Distinct overloads are all retained.
+        if !entry.iter().any(|d| d.value == def.value) {
+            entry.push(def);
+        }
+        self.bump_generation();
         Ok(())
     }
 
+    /// Import multiple definitions into the scope at once.
+    ///
+    /// Behaves like calling `import_def` for every definition, but only
+    /// borrows the imported definitions once. Each definition is recorded at
+    /// the span of its name.
+    pub fn import_defs(
+        &self,
+        defs: impl IntoIterator<Item = (Spanned<ResolvableName>, Def2<'t>)>,
+        ctx: &SessionContext,
+    ) {
+        let mut imported_defs = self.imported_defs.borrow_mut();
+        for (name, def) in defs {
+            if ctx.has_verbosity(Verbosity::NAMES) {
+                ctx.emit(
+                    DiagBuilder2::note(format!("import `{}` as {:?}", name.value, def))
+                        .span(name.span),
+                );
+            }
+            debugln!("import `{}` as {:?}", name.value, def);
+            let entry = imported_defs
+                .entry(name.value)
+                .or_insert_with(|| Vec::new());
+            if !entry.iter().any(|d| d.value == def) {
+                entry.push(Spanned::new(def, name.span));
+            }
+        }
+        self.bump_generation();
+    }
+
     /// Import an entire scope into the scope.
-    pub fn import_scope(&self, scope: &'t ScopeData<'t>) -> Result<()> {
+    ///
+    /// The `span` is the location of the use clause that caused the import.
+    pub fn import_scope(
+        &self,
+        scope: &'t ScopeData<'t>,
+        span: Span,
+        ctx: &SessionContext,
+    ) -> Result<()> {
+        if ctx.has_verbosity(Verbosity::NAMES) {
+            ctx.emit(
+                DiagBuilder2::note(format!("import scope {:?}", scope as *const _)).span(span),
+            );
+        }
+        debugln!("import scope {:?}", scope as *const _);
+        // Importing a scope into itself has no effect.
+        if scope as *const _ == self as *const _ {
+            return Ok(());
+        }
         self.imported_scopes.borrow_mut().insert(scope);
+        self.import_spans
+            .borrow_mut()
+            .entry((ScopeId::of(scope), None))
+            .or_insert(span);
+        self.bump_generation();
+        Ok(())
+    }
+
+    /// Import a selection of names from another scope into the scope.
+    ///
+    /// Only the names are recorded, not the definitions themselves. The
+    /// definitions are looked up in `scope` whenever a name is resolved.
+    pub fn import_scope_selective(
+        &self,
+        scope: &'t ScopeData<'t>,
+        names: &[ResolvableName],
+        span: Span,
+        ctx: &SessionContext,
+    ) -> Result<()> {
+        for name in names {
+            if ctx.has_verbosity(Verbosity::NAMES) {
+                ctx.emit(
+                    DiagBuilder2::note(format!(
+                        "import `{}` from scope {:?}",
+                        name, scope as *const _
+                    ))
+                    .span(span),
+                );
+            }
+            debugln!("import `{}` from scope {:?}", name, scope as *const _);
+        }
+        self.selected_scopes
+            .borrow_mut()
+            .entry(scope)
+            .or_insert_with(|| HashSet::new())
+            .extend(names.iter().cloned());
+        let mut spans = self.import_spans.borrow_mut();
+        for &name in names {
+            spans
+                .entry((ScopeId::of(scope), Some(name)))
+                .or_insert(span);
+        }
+        self.bump_generation();
         Ok(())
     }
 
+    /// Emit a warning for every imported scope or name that was never used.
+    ///
+    /// This should be called once all names in the scope have been resolved.
+    pub fn warn_unused_imports(&self, ctx: &SessionContext) {
+        let used = self.used_imports.borrow();
+        let mut unused: Vec<_> = self
+            .import_spans
+            .borrow()
+            .iter()
+            .filter(|&(key, _)| !used.contains(key))
+            .map(|(&(_, name), &span)| (span, name))
+            .collect();
+        unused.sort_by_key(|&(span, _)| span);
+        for (span, name) in unused {
+            let msg = match name {
+                Some(name) => format!("`{}` is imported but never used", name),
+                None => format!("nothing is used from `{}`", span.extract()),
+            };
+            ctx.emit(DiagBuilder2::warning(msg).span(span));
+        }
+    }
+
+    /// Take a snapshot of the definitions in the scope.
+    ///
+    /// The returned mark can be passed to `rollback` to undo all definitions
+    /// and imported definitions that have been added since.
+    pub fn mark(&self) -> ScopeMark {
+        ScopeMark {
+            defs: mark_defs(&self.defs.borrow()),
+            imported_defs: mark_defs(&self.imported_defs.borrow()),
+        }
+    }
+
+    /// Undo all definitions added to the scope since a mark was taken.
+    pub fn rollback(&self, mark: ScopeMark) {
+        rollback_defs(&mut self.defs.borrow_mut(), &mark.defs);
+        rollback_defs(&mut self.imported_defs.borrow_mut(), &mark.imported_defs);
+        self.bump_generation();
+    }
+
+    /// List the definitions in this scope.
+    ///
+    /// If `include_imports` is set, the definitions made visible by use
+    /// clauses are listed as well. If `include_parents` is set, the parent
+    /// scopes are listed as well. The definitions are ordered by their
+    /// location in the source.
+    pub fn iter_defs(
+        &self,
+        include_imports: bool,
+        include_parents: bool,
+    ) -> Vec<(ResolvableName, Spanned<Def2<'t>>)> {
+        let mut found: Vec<_> = flatten_defs(&self.defs.borrow()).collect();
+        if include_imports {
+            found.extend(flatten_defs(&self.imported_defs.borrow()));
+            for (&s, names) in self.selected_scopes.borrow().iter() {
+                found.extend(flatten_defs(&s.defs.borrow()).filter(|(n, _)| names.contains(n)));
+            }
+            for &s in self.imported_scopes.borrow().iter() {
+                found.extend(flatten_defs(&s.defs.borrow()));
+            }
+        }
+        match self.parent {
+            Some(parent) if include_parents => {
+                found.extend(parent.iter_defs(include_imports, include_parents))
+            }
+            _ => (),
+        }
+        found.sort_by_key(|(_, def)| def.span);
+        found
+    }
+
+    /// Collect the definitions visible in this scope.
+    ///
+    /// Materializes the names visible in this scope, its imports, and its
+    /// parents into a single map, following the same visibility rules as
+    /// `resolve`. Every name maps to the definitions `resolve` would return
+    /// for it. Unlike `resolve`, this does not mark any imports as used.
+    pub fn flatten(&self) -> HashMap<ResolvableName, Vec<Spanned<Def2<'t>>>> {
+        let mut visible = match self.parent {
+            Some(parent) => parent.flatten(),
+            None => HashMap::new(),
+        };
+
+        // Names imported as part of an entire scope.
+        let mut imported: HashMap<_, Vec<_>> = HashMap::new();
+        let mut visited: HashSet<*const ScopeData<'t>> = HashSet::new();
+        visited.insert(self);
+        for &s in self.imported_scopes.borrow().iter() {
+            if visited.insert(s) {
+                for (name, def) in s.local_defs() {
+                    imported.entry(name).or_default().push(def);
+                }
+            }
+        }
+
+        // Names imported individually.
+        let mut selected: HashMap<_, Vec<_>> = HashMap::new();
+        for (name, def) in flatten_defs(&self.imported_defs.borrow()) {
+            selected.entry(name).or_default().push(def);
+        }
+        let mut visited: HashSet<*const ScopeData<'t>> = HashSet::new();
+        visited.insert(self);
+        for (&s, names) in self.selected_scopes.borrow().iter() {
+            if visited.insert(s) {
+                for (name, def) in s.local_defs() {
+                    if names.contains(&name) {
+                        selected.entry(name).or_default().push(def);
+                    }
+                }
+            }
+        }
+
+        // Each tier hides the names of the tiers before it.
+        visible.extend(imported);
+        visible.extend(selected);
+        let mut local: HashMap<_, Vec<_>> = HashMap::new();
+        for (name, def) in self.local_defs() {
+            local.entry(name).or_default().push(def);
+        }
+        visible.extend(local);
+        visible
+    }
+
+    /// Find the name under which a definition was made in this scope.
+    ///
+    /// This is the inverse of `resolve`, but only considers the definitions
+    /// made directly in this scope. Definitions are compared by identity. If
+    /// the same definition was made under multiple names, the one that appears
+    /// first in the source is returned. Aliases are separate definitions, such
+    /// that looking up the target of an alias yields its declared name.
+    pub fn name_of(&self, def: &Def2<'t>) -> Option<ResolvableName> {
+        flatten_defs(&self.defs.borrow())
+            .filter(|(_, d)| d.value == *def)
+            .min_by_key(|(_, d)| d.span)
+            .map(|(name, _)| name)
+    }
+
+    /// Suggest a visible name that is similar to `name`.
+    ///
+    /// This is useful to point out typos when `name` cannot be resolved. The
+    /// names defined in this scope, imported into it, and visible from its
+    /// parent scopes are compared to `name` by their edit distance, and the
+    /// closest one is returned if it is close enough.
+    pub fn suggest(&self, name: ResolvableName) -> Option<ResolvableName> {
+        let name_str = match name {
+            ResolvableName::Ident(n) => n.as_str().to_lowercase(),
+            _ => return None,
+        };
+        let threshold = std::cmp::max(1, name_str.len() / 3);
+        self.iter_defs(true, true)
+            .into_iter()
+            .flat_map(|(candidate, _)| match candidate {
+                ResolvableName::Ident(n) => Some((
+                    edit_distance(&name_str, &n.as_str().to_lowercase()),
+                    candidate,
+                )),
+                _ => None,
+            })
+            .filter(|&(distance, _)| distance > 0 && distance <= threshold)
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, candidate)| candidate)
+    }
+
     /// Find a name in this scope.
     ///
-    /// This only searches this scope and does not proceed to parent or child
-    /// scopes. Use a dedicated name resolver for that.
+    /// Implements the VHDL visibility rules: Definitions made directly in this
+    /// scope hide any imported definitions of the same name. Names imported
+    /// individually, e.g. `use pkg.X`, in turn hide names imported as part of
+    /// an entire scope, e.g. `use pkg.all`. The definitions made visible by
+    /// the different use clauses of one kind are merged, such that the caller
+    /// may perform overload resolution on them. If `recur` is set and
+    /// the name is not visible in this scope at all, the parent scope is
+    /// searched. The caller is responsible for reporting ambiguities among the
+    /// returned definitions.
     pub fn resolve(&self, name: ResolvableName, recur: bool) -> Vec<Spanned<Def2<'t>>> {
-        let mut found = Vec::new();
-        found.extend(
-            self.defs
-                .borrow()
-                .get(&name)
+        self.resolve_with_scope(name, recur)
+            .into_iter()
+            .map(|(def, _)| def)
+            .collect()
+    }
+
+    /// Find a name in this scope, and the scope that provides each definition.
+    ///
+    /// Same as `resolve`, but additionally returns the scope each definition
+    /// was made in. For definitions that were imported individually this is
+    /// the scope they were imported into. This allows diagnostics to point out
+    /// where competing definitions come from.
+    pub fn resolve_with_scope<'s>(
+        &'s self,
+        name: ResolvableName,
+        recur: bool,
+    ) -> Vec<(Spanned<Def2<'t>>, &'s ScopeData<'t>)> {
+        let found = self.resolve_local(name);
+        if !found.is_empty() {
+            return found;
+        }
+        let found = self.resolve_selected_defs(name);
+        if !found.is_empty() {
+            return found;
+        }
+        let found = self.resolve_imported(name);
+        if !found.is_empty() {
+            return found;
+        }
+        match self.parent {
+            Some(parent) if recur => parent.resolve_with_scope(name, recur),
+            _ => vec![],
+        }
+    }
+
+    /// Find a name in this scope, and how each definition became visible.
+    ///
+    /// Same as `resolve`, but additionally returns the origin of each
+    /// definition. This is intended for diagnostics and tools that visualize
+    /// visibility, and does not affect which definitions are found.
+    pub fn resolve_with_origin<'s>(
+        &'s self,
+        name: ResolvableName,
+        recur: bool,
+    ) -> Vec<(Spanned<Def2<'t>>, ResolutionOrigin<'s, 't>)> {
+        let mut scope = self;
+        let mut hops = 0;
+        loop {
+            let found = scope.resolve_local(name);
+            if !found.is_empty() {
+                return with_origin(found, hops, |_| ResolutionOrigin::Local);
+            }
+            let found = scope.resolve_selected_defs(name);
+            if !found.is_empty() {
+                return with_origin(found, hops, |_| ResolutionOrigin::ImportedDef);
+            }
+            let found = scope.resolve_imported(name);
+            if !found.is_empty() {
+                return with_origin(found, hops, ResolutionOrigin::ImportedScope);
+            }
+            match scope.parent {
+                Some(parent) if recur => {
+                    scope = parent;
+                    hops += 1;
+                }
+                _ => return vec![],
+            }
+        }
+    }
+
+    /// Resolve a selected name such as `work.pkg.thing`.
+    ///
+    /// The first segment of the path is resolved in this scope and its
+    /// parents. Every following segment is resolved in the library or package
+    /// the previous segment refers to. Every segment must resolve to exactly
+    /// one definition, otherwise an error pointing at that segment is emitted.
+    pub fn resolve_selected(
+        &self,
+        path: &[Spanned<ResolvableName>],
+        ctx: &SessionContext,
+    ) -> Result<Spanned<Def2<'t>>> {
+        assert!(!path.is_empty(), "empty selected name");
+        let mut defs = self.resolve(path[0].value, true);
+        let mut scope = self;
+        for (index, &name) in path.iter().enumerate() {
+            if index > 0 {
+                let prev = path[index - 1];
+                scope = match defs[0].value {
+                    Def2::Lib(x) => x.scope(),
+                    Def2::Pkg(x) => x.poll()?.scope(),
+                    Def2::PkgInst(x) => x.scope(),
+                    def => {
+                        ctx.emit(
+                            DiagBuilder2::error(format!(
+                                "cannot select `{}` from `{}`",
+                                name.value, prev.value
+                            ))
+                            .span(name.span)
+                            .add_note(format!(
+                                "`{}` is a {}, not a library or package",
+                                prev.value,
+                                def.desc_kind()
+                            ))
+                            .span(prev.span),
+                        );
+                        return Err(());
+                    }
+                };
+                defs = scope.resolve(name.value, false);
+            }
+            if defs.is_empty() {
+                let mut d =
+                    DiagBuilder2::error(format!("`{}` is unknown", name.value)).span(name.span);
+                if let Some(similar) = scope.suggest(name.value) {
+                    d = d.add_note(format!("Did you mean `{}`?", similar));
+                }
+                ctx.emit(d);
+                return Err(());
+            }
+            if defs.len() > 1 {
+                ctx.emit(ambiguity_error(name, &defs));
+                return Err(());
+            }
+        }
+        Ok(defs[0])
+    }
+
+    /// Print the contents of the scope for debugging.
+    ///
+    /// Lists the definitions made in the scope, the definitions imported
+    /// individually, and the scopes imported by use clauses, followed by the
+    /// imported scopes and the parent scope in turn. Scopes are identified by
+    /// a short id derived from their address. Every scope is printed at most
+    /// once, such that cycles in the import graph do not cause the dump to
+    /// recurse forever.
+    pub fn dump(&self, out: &mut impl Write, indent: usize) -> io::Result<()> {
+        self.dump_visited(out, indent, &mut HashSet::new())
+    }
+
+    fn dump_visited(
+        &self,
+        out: &mut impl Write,
+        indent: usize,
+        visited: &mut HashSet<*const ScopeData<'t>>,
+    ) -> io::Result<()> {
+        let pad = "  ".repeat(indent);
+        if !visited.insert(self) {
+            return writeln!(out, "{}scope {} (see above)", pad, short_id(self));
+        }
+        writeln!(
+            out,
+            "{}scope {} (generation {})",
+            pad,
+            short_id(self),
+            self.generation()
+        )?;
+        dump_defs(out, &pad, "defs", &self.defs.borrow())?;
+        dump_defs(out, &pad, "imported defs", &self.imported_defs.borrow())?;
+
+        // List the use clauses before descending into the imported scopes, such
+        // that they remain readable next to the definitions.
+        let mut imported: Vec<&'t ScopeData<'t>> = vec![];
+        for &scope in self.imported_scopes.borrow().iter() {
+            writeln!(out, "{}  use {}.all", pad, short_id(scope))?;
+            imported.push(scope);
+        }
+        for (&scope, names) in self.selected_scopes.borrow().iter() {
+            let mut names: Vec<_> = names.iter().map(|n| n.to_string()).collect();
+            names.sort();
+            writeln!(
+                out,
+                "{}  use {}.{{{}}}",
+                pad,
+                short_id(scope),
+                names.join(", ")
+            )?;
+            imported.push(scope);
+        }
+        for scope in imported {
+            scope.dump_visited(out, indent + 1, visited)?;
+        }
+        if let Some(parent) = self.parent {
+            writeln!(out, "{}  parent:", pad)?;
+            parent.dump_visited(out, indent + 2, visited)?;
+        }
+        Ok(())
+    }
+
+    /// Find a name among the definitions made directly in this scope.
+    ///
+    /// Aliases are replaced with the definitions they denote. The targets keep
+    /// the location of the alias, such that diagnostics point at the name that
+    /// was actually declared in this scope.
+    fn resolve_local<'s>(
+        &'s self,
+        name: ResolvableName,
+    ) -> Vec<(Spanned<Def2<'t>>, &'s ScopeData<'t>)> {
+        match self.defs.borrow().get(&name) {
+            Some(defs) => expand_aliases(defs)
                 .into_iter()
-                .flat_map(|d| d.iter()),
-        );
+                .map(|def| (def, self))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// Collect the definitions made directly in this scope.
+    ///
+    /// Aliases are replaced with the definitions they denote, as in
+    /// `resolve_local`.
+    fn local_defs(&self) -> Vec<(ResolvableName, Spanned<Def2<'t>>)> {
+        self.defs
+            .borrow()
+            .iter()
+            .flat_map(|(&name, defs)| expand_aliases(defs).into_iter().map(move |def| (name, def)))
+            .collect()
+    }
+
+    /// Find a name among the definitions imported individually into this
+    /// scope.
+    ///
+    /// Every selectively imported scope is consulted once, since the selected
+    /// names are grouped by scope.
+    fn resolve_selected_defs<'s>(
+        &'s self,
+        name: ResolvableName,
+    ) -> Vec<(Spanned<Def2<'t>>, &'s ScopeData<'t>)> {
+        let mut found = Vec::new();
         found.extend(
             self.imported_defs
                 .borrow()
                 .get(&name)
                 .into_iter()
-                .flat_map(|d| d.iter()),
+                .flat_map(|d| d.iter().map(|&def| (def, self))),
         );
-        for s in self.imported_scopes.borrow().iter() {
-            found.extend(
-                s.defs
-                    .borrow()
-                    .get(&name)
-                    .into_iter()
-                    .flat_map(|d| d.iter()),
+        for (&s, names) in self.selected_scopes.borrow().iter() {
+            if names.contains(&name) {
+                let defs = s.resolve_local(name);
+                if !defs.is_empty() {
+                    self.mark_used((ScopeId::of(s), Some(name)));
+                }
+                found.extend(defs);
+            }
+        }
+        found
+    }
+
+    /// Find a name among the scopes imported entirely into this scope.
+    ///
+    /// Every imported scope is consulted once, even if it has been imported by
+    /// multiple use clauses. Only the definitions made directly in an imported
+    /// scope are considered, since use clauses are not transitive: the names
+    /// an imported scope makes visible through its own use clauses are not
+    /// visible here. This also keeps resolution from recursing forever if the
+    /// imported scope imports this scope in turn.
+    fn resolve_imported<'s>(
+        &'s self,
+        name: ResolvableName,
+    ) -> Vec<(Spanned<Def2<'t>>, &'s ScopeData<'t>)> {
+        let mut found = Vec::new();
+        for &s in self.imported_scopes.borrow().iter() {
+            let defs = s.resolve_local(name);
+            if !defs.is_empty() {
+                self.mark_used((ScopeId::of(s), None));
+            }
+            found.extend(defs);
+        }
+        found
+    }
+
+    /// Record that an import has been used to resolve a name.
+    ///
+    /// The write lock is only taken the first time an import is used, such
+    /// that repeated lookups through the same import can proceed concurrently.
+    fn mark_used(&self, key: (ScopeId, Option<ResolvableName>)) {
+        if !self.used_imports.borrow().contains(&key) {
+            self.used_imports.borrow_mut().insert(key);
+        }
+    }
+}
+
+/// Attach an origin to the definitions found in a scope.
+///
+/// Definitions found after traversing at least one parent link are reported
+/// as coming from that parent, regardless of how they are visible there.
+fn with_origin<'s, 't>(
+    found: Vec<(Spanned<Def2<'t>>, &'s ScopeData<'t>)>,
+    hops: u32,
+    origin: impl Fn(&'s ScopeData<'t>) -> ResolutionOrigin<'s, 't>,
+) -> Vec<(Spanned<Def2<'t>>, ResolutionOrigin<'s, 't>)> {
+    found
+        .into_iter()
+        .map(|(def, scope)| match hops {
+            0 => (def, origin(scope)),
+            _ => (def, ResolutionOrigin::Parent(hops)),
+        })
+        .collect()
+}
+
+/// How a resolved name became visible in the scope it was resolved in.
+///
+/// See `ScopeData::resolve_with_origin`.
+#[derive(Copy, Clone)]
+pub enum ResolutionOrigin<'s, 't> {
+    /// The name is defined directly in the scope.
+    Local,
+    /// The name has been imported individually, e.g. by `use pkg.X`.
+    ImportedDef,
+    /// The name is defined in a scope imported entirely, e.g. by
+    /// `use pkg.all`.
+    ImportedScope(&'s ScopeData<'t>),
+    /// The name is visible in a parent scope. Contains the number of parent
+    /// links traversed before the name was found.
+    Parent(u32),
+}
+
+impl fmt::Debug for ResolutionOrigin<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ResolutionOrigin::Local => write!(f, "Local"),
+            ResolutionOrigin::ImportedDef => write!(f, "ImportedDef"),
+            ResolutionOrigin::ImportedScope(s) => write!(f, "ImportedScope({:?})", s as *const _),
+            ResolutionOrigin::Parent(hops) => write!(f, "Parent({})", hops),
+        }
+    }
+}
+
+impl PartialEq for ResolutionOrigin<'_, '_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (*self, *other) {
+            (ResolutionOrigin::Local, ResolutionOrigin::Local) => true,
+            (ResolutionOrigin::ImportedDef, ResolutionOrigin::ImportedDef) => true,
+            (ResolutionOrigin::ImportedScope(a), ResolutionOrigin::ImportedScope(b)) => {
+                a as *const _ == b as *const _
+            }
+            (ResolutionOrigin::Parent(a), ResolutionOrigin::Parent(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ResolutionOrigin<'_, '_> {}
+
+/// Create an error for a name that resolves to multiple definitions.
+///
+/// Each of the candidates is listed in a separate note pointing at its
+/// declaration. Builtin definitions without a declaration are omitted.
+pub fn ambiguity_error(
+    name: Spanned<ResolvableName>,
+    candidates: &[Spanned<Def2>],
+) -> DiagBuilder2 {
+    let mut d = DiagBuilder2::error(format!("`{}` is ambiguous", name.value)).span(name.span);
+    for candidate in candidates {
+        if candidate.span != INVALID_SPAN {
+            d = d.add_note("Candidate declared here:").span(candidate.span);
+        }
+    }
+    d
+}
+
+/// A cache of name resolution results.
+///
+/// Resolving a name walks the imported and parent scopes every time, which
+/// adds up when the same names are resolved over and over again during type
+/// checking. This cache memoizes the result of `ScopeData::resolve` per scope
+/// and name.
+///
+/// The cache does not track modifications of the scopes. Defining or
+/// importing a name, or rolling back a scope, may change the result of
+/// resolving names in that scope, in any of its subscopes, and in any scope
+/// that imports it. The cache must therefore only be used once all
+/// definitions have been made, or be cleared with `clear` whenever a scope is
+/// modified. `ScopeData::generation` can be used to detect such modifications.
+#[derive(Debug, Default)]
+pub struct ResolveCache<'t> {
+    entries: RefCell<HashMap<(*const ScopeData<'t>, ResolvableName, bool), Vec<Spanned<Def2<'t>>>>>,
+    hits: Cell<usize>,
+}
+
+impl<'t> ResolveCache<'t> {
+    /// Create a new empty cache.
+    pub fn new() -> ResolveCache<'t> {
+        Default::default()
+    }
+
+    /// Find a name in a scope, reusing the result of a previous lookup.
+    ///
+    /// See `ScopeData::resolve`.
+    pub fn resolve(
+        &self,
+        scope: &ScopeData<'t>,
+        name: ResolvableName,
+        recur: bool,
+    ) -> Vec<Spanned<Def2<'t>>> {
+        let key = (scope as *const _, name, recur);
+        if let Some(defs) = self.entries.borrow().get(&key) {
+            self.hits.set(self.hits.get() + 1);
+            return defs.clone();
+        }
+        let defs = scope.resolve(name, recur);
+        self.entries.borrow_mut().insert(key, defs.clone());
+        defs
+    }
+
+    /// Discard all cached results.
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear();
+    }
+
+    /// Return the number of lookups that were served from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits.get()
+    }
+}
+
+/// A snapshot of the definitions in a scope.
+///
+/// Records the number of definitions per name, which is enough to undo any
+/// definitions made afterwards. See `ScopeData::mark` and
+/// `ScopeData::rollback`.
+#[derive(Clone, Debug)]
+pub struct ScopeMark {
+    defs: HashMap<ResolvableName, usize>,
+    imported_defs: HashMap<ResolvableName, usize>,
+}
+
+/// Derive a short id from the address of a scope, for use in dumps.
+fn short_id(scope: *const ScopeData) -> String {
+    format!("#{:04x}", (scope as usize >> 3) & 0xffff)
+}
+
+/// Print the definitions of a scope sorted by name, for use in dumps.
+fn dump_defs(
+    out: &mut impl Write,
+    pad: &str,
+    title: &str,
+    defs: &HashMap<ResolvableName, Vec<Spanned<Def2>>>,
+) -> io::Result<()> {
+    if defs.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "{}  {}:", pad, title)?;
+    let mut defs: Vec<_> = defs.iter().map(|(name, d)| (name.to_string(), d)).collect();
+    defs.sort_by(|a, b| a.0.cmp(&b.0));
+    for (name, d) in defs {
+        // Print the definitions without lowering the nodes they point to, such
+        // that dumping a scope has no side effects.
+        let rendered: Vec<_> = d.iter().map(|def| format!("{:?}", def.value)).collect();
+        writeln!(out, "{}    {}: {}", pad, name, rendered.join(", "))?;
+    }
+    Ok(())
+}
+
+/// Compute the Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let next = std::cmp::min(
+                std::cmp::min(row[j] + 1, row[j + 1] + 1),
+                prev + if ca == cb { 0 } else { 1 },
             );
+            prev = row[j + 1];
+            row[j + 1] = next;
         }
-        if found.is_empty() && self.parent.is_some() {
-            self.parent.unwrap().resolve(name, recur)
-        } else {
-            found
+    }
+    row[b.len()]
+}
+
+/// Replace aliases with the definitions they denote.
+///
+/// The targets keep the location of the alias.
+fn expand_aliases<'t>(defs: &[Spanned<Def2<'t>>]) -> Vec<Spanned<Def2<'t>>> {
+    let mut found = Vec::new();
+    for &def in defs {
+        match def.value {
+            Def2::Alias(alias) => found.extend(
+                alias
+                    .targets()
+                    .iter()
+                    .map(|target| Spanned::new(target.value, def.span)),
+            ),
+            _ => found.push(def),
         }
     }
+    found
+}
+
+fn flatten_defs<'a, 't: 'a>(
+    defs: &'a HashMap<ResolvableName, Vec<Spanned<Def2<'t>>>>,
+) -> impl Iterator<Item = (ResolvableName, Spanned<Def2<'t>>)> + 'a {
+    defs.iter()
+        .flat_map(|(&name, d)| d.iter().map(move |&def| (name, def)))
+}
+
+fn mark_defs<T>(defs: &HashMap<ResolvableName, Vec<T>>) -> HashMap<ResolvableName, usize> {
+    defs.iter().map(|(&name, d)| (name, d.len())).collect()
+}
+
+fn rollback_defs<T>(
+    defs: &mut HashMap<ResolvableName, Vec<T>>,
+    mark: &HashMap<ResolvableName, usize>,
+) {
+    defs.retain(|name, d| match mark.get(name) {
+        Some(&len) => {
+            d.truncate(len);
+            true
+        }
+        None => false,
+    });
 }
 
 impl<'t> PartialEq for &'t ScopeData<'t> {
@@ -233,12 +1185,30 @@ pub trait ScopeContext<'t> {
     /// Import a definition into the scope.
     fn import_def(&self, name: ResolvableName, def: Spanned<Def2<'t>>) -> Result<()>;
     /// Import an entire scope into the scope.
-    fn import_scope(&self, scope: &'t ScopeData<'t>) -> Result<()>;
+    fn import_scope(&self, scope: &'t ScopeData<'t>, span: Span) -> Result<()>;
+    /// Import a selection of names from another scope into the scope.
+    fn import_scope_selective(
+        &self,
+        scope: &'t ScopeData<'t>,
+        names: &[ResolvableName],
+        span: Span,
+    ) -> Result<()>;
     /// Find a name in this scope.
     fn resolve(&self, name: ResolvableName, recur: bool) -> Vec<Spanned<Def2<'t>>>;
+    /// Find a name in this scope, and the scope that provides each definition.
+    fn resolve_with_scope(
+        &self,
+        name: ResolvableName,
+        recur: bool,
+    ) -> Vec<(Spanned<Def2<'t>>, &'t ScopeData<'t>)>;
 }
 
 /// An enumeration variant or physical unit.
+///
+/// Identifies the type declaration that declares the variant, and the index of
+/// the variant within that declaration. Literals of the same name declared by
+/// different enumeration types are thus distinct definitions, and overload
+/// resolution can pick the one of the expected type.
 #[derive(Copy, Clone)]
 pub struct TypeVariantDef<'t>(pub &'t hir::LatentNode<'t, hir::TypeDecl2<'t>>, pub usize);
 
@@ -250,8 +1220,428 @@ impl<'t> fmt::Debug for TypeVariantDef<'t> {
 
 impl<'t> PartialEq for TypeVariantDef<'t> {
     fn eq(&self, other: &Self) -> bool {
-        self.0 as *const _ == other.0 as *const _ && self.1 == other.1
+        same_node(self.0, other.0) && self.1 == other.1
     }
 }
 
 impl<'t> Eq for TypeVariantDef<'t> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arenas::Alloc;
+    use crate::common::name::get_name_table;
+    use crate::common::source::get_source_manager;
+    use crate::common::Session;
+    use crate::hir::{AliasDecl2, AllocContext, Arenas2, FromAst, Library, TypeDecl2};
+    use crate::syntax::ast;
+
+    #[test]
+    fn resolve_cache_hit() {
+        let sess = Session::new();
+        let arenas = Arenas2::new();
+        let ctx = AllocContext {
+            sess: &sess,
+            arenas: &arenas,
+            scope: arenas.alloc(ScopeData::root()),
+        };
+        let lib = Library::new(get_name_table().intern("magic", true), &[], ctx).unwrap();
+        let scope = ScopeData::new(lib.scope());
+        let name = get_name_table().intern("WORK", false).into();
+        let cache = ResolveCache::new();
+        let first = cache.resolve(&scope, name, true);
+        assert_eq!(first.len(), 1);
+        assert_eq!(cache.hits(), 0);
+        let second = cache.resolve(&scope, name, true);
+        assert_eq!(cache.hits(), 1);
+        assert!(first
+            .iter()
+            .map(|d| d.value)
+            .eq(second.iter().map(|d| d.value)));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn scope_cell_shared_across_threads() {
+        use std::sync::Arc;
+        let cell = Arc::new(ScopeCell::new(HashMap::new()));
+        cell.borrow_mut().insert(1, 2);
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let cell = cell.clone();
+                std::thread::spawn(move || cell.borrow().get(&1).cloned())
+            })
+            .collect();
+        for reader in readers {
+            assert_eq!(reader.join().unwrap(), Some(2));
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn import_bookkeeping_is_sync() {
+        fn assert_sync<T: Send + Sync>() {}
+        assert_sync::<ScopeCell<HashMap<(ScopeId, Option<ResolvableName>), Span>>>();
+        assert_sync::<ScopeCell<HashSet<(ScopeId, Option<ResolvableName>)>>>();
+    }
+
+    #[test]
+    fn generation_bumped_on_modification() {
+        let sess = Session::new();
+        let arenas = Arenas2::new();
+        let ctx = AllocContext {
+            sess: &sess,
+            arenas: &arenas,
+            scope: arenas.alloc(ScopeData::root()),
+        };
+        let lib = Library::new(get_name_table().intern("magic", true), &[], ctx).unwrap();
+        let scope = ScopeData::new(lib.scope());
+        let name = Spanned::new(get_name_table().intern("FOO", false).into(), INVALID_SPAN);
+        let initial = scope.generation();
+        scope.define(name, Def2::Lib(lib), &sess).unwrap();
+        let defined = scope.generation();
+        assert!(defined > initial);
+        scope
+            .import_scope(lib.scope(), INVALID_SPAN, &sess)
+            .unwrap();
+        assert!(scope.generation() > defined);
+        let imported = scope.generation();
+        scope.resolve(name.value, true);
+        assert_eq!(scope.generation(), imported);
+    }
+
+    #[test]
+    fn undefine_keeps_other_defs() {
+        let type_decl = |name| ast::TypeDecl {
+            id: ast::DUMMY_NODE_ID,
+            span: INVALID_SPAN,
+            name: Spanned::new(get_name_table().intern(name, false), INVALID_SPAN),
+            data: None,
+        };
+        let (level_decl, drive_decl) = (type_decl("LEVEL"), type_decl("DRIVE"));
+        let sess = Session::new();
+        let arenas = Arenas2::new();
+        let ctx = AllocContext {
+            sess: &sess,
+            arenas: &arenas,
+            scope: arenas.alloc(ScopeData::root()),
+        };
+        let level = TypeDecl2::alloc_slot(&level_decl, ctx).unwrap();
+        let drive = TypeDecl2::alloc_slot(&drive_decl, ctx).unwrap();
+
+        // Two overloads of `LOW`, and an import that they hide.
+        let low = ResolvableName::from(get_name_table().intern("LOW", false));
+        let source = get_source_manager().add_anonymous("LOW LOW");
+        let (first, second) = (Span::new(source, 0, 3), Span::new(source, 4, 7));
+        let scope = ScopeData::new(ctx.scope);
+        let level_low = Def2::Enum(TypeVariantDef(level, 0));
+        let drive_low = Def2::Enum(TypeVariantDef(drive, 0));
+        scope
+            .define(Spanned::new(low, first), level_low, &sess)
+            .unwrap();
+        scope
+            .define(Spanned::new(low, second), drive_low, &sess)
+            .unwrap();
+        scope
+            .import_def(low, Spanned::new(Def2::Type(level), INVALID_SPAN), &sess)
+            .unwrap();
+        assert_eq!(scope.resolve(low, false).len(), 2);
+
+        scope.undefine_one(low, first);
+        let found = scope.resolve(low, false);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].value == drive_low);
+
+        scope.undefine(low);
+        let found = scope.resolve(low, false);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].value == Def2::Type(level));
+    }
+
+    #[test]
+    fn resolve_selected_segments() {
+        let sess = Session::new();
+        let arenas = Arenas2::new();
+        let ctx = AllocContext {
+            sess: &sess,
+            arenas: &arenas,
+            scope: arenas.alloc(ScopeData::root()),
+        };
+        let lib = Library::new(get_name_table().intern("magic", true), &[], ctx).unwrap();
+        let scope = ScopeData::new(lib.scope());
+        // The unknown segment is reported, which requires a valid location.
+        let source = get_source_manager().add_anonymous("WORK.BOGUS");
+        let work = Spanned::new(
+            get_name_table().intern("WORK", false).into(),
+            Span::new(source, 0, 4),
+        );
+        let bogus = Spanned::new(
+            get_name_table().intern("BOGUS", false).into(),
+            Span::new(source, 5, 10),
+        );
+        match scope.resolve_selected(&[work], &sess) {
+            Ok(Spanned {
+                value: Def2::Lib(x),
+                ..
+            }) => assert_eq!(x as *const _, lib as *const _),
+            x => panic!("expected library, got {:?}", x),
+        }
+        assert!(scope.resolve_selected(&[work, work], &sess).is_ok());
+        assert!(scope.resolve_selected(&[work, bogus], &sess).is_err());
+    }
+
+    #[test]
+    fn resolve_origin_and_hops() {
+        let sess = Session::new();
+        let arenas = Arenas2::new();
+        let ctx = AllocContext {
+            sess: &sess,
+            arenas: &arenas,
+            scope: arenas.alloc(ScopeData::root()),
+        };
+        let lib = Library::new(get_name_table().intern("magic", true), &[], ctx).unwrap();
+        let outer: &ScopeData = arenas.alloc(ScopeData::new(lib.scope()));
+        let name = Spanned::new(get_name_table().intern("FOO", false).into(), INVALID_SPAN);
+        outer.define(name, Def2::Lib(lib), &sess).unwrap();
+        let origins = |scope: &ScopeData| -> Vec<_> {
+            scope
+                .resolve_with_origin(name.value, true)
+                .into_iter()
+                .map(|(_, origin)| format!("{:?}", origin))
+                .collect()
+        };
+        assert_eq!(origins(outer), vec!["Local"]);
+
+        let middle = ScopeData::new(outer);
+        let inner = ScopeData::new(&middle);
+        assert_eq!(origins(&middle), vec!["Parent(1)"]);
+        assert_eq!(origins(&inner), vec!["Parent(2)"]);
+        assert!(inner.resolve_with_origin(name.value, false).is_empty());
+
+        let other = ScopeData::new(lib.scope());
+        other.import_scope(outer, INVALID_SPAN, &sess).unwrap();
+        match other.resolve_with_origin(name.value, false)[..] {
+            [(_, ResolutionOrigin::ImportedScope(s))] => {
+                assert_eq!(s as *const _, outer as *const _)
+            }
+            ref x => panic!("expected import from scope, got {:?}", x),
+        }
+    }
+
+    #[test]
+    fn resolve_terminates_on_import_cycle() {
+        let type_decl = |name| ast::TypeDecl {
+            id: ast::DUMMY_NODE_ID,
+            span: INVALID_SPAN,
+            name: Spanned::new(get_name_table().intern(name, false), INVALID_SPAN),
+            data: None,
+        };
+        let (byte_decl, short_decl) = (type_decl("BYTE"), type_decl("SHORT"));
+        let sess = Session::new();
+        let arenas = Arenas2::new();
+        let ctx = AllocContext {
+            sess: &sess,
+            arenas: &arenas,
+            scope: arenas.alloc(ScopeData::root()),
+        };
+        let lib = Library::new(get_name_table().intern("magic", true), &[], ctx).unwrap();
+        let ctx_a = AllocContext {
+            scope: arenas.alloc(ScopeData::new(lib.scope())),
+            ..ctx
+        };
+        let ctx_b = AllocContext {
+            scope: arenas.alloc(ScopeData::new(lib.scope())),
+            ..ctx
+        };
+        let byte = TypeDecl2::alloc_slot(&byte_decl, ctx_a).unwrap();
+        let short = TypeDecl2::alloc_slot(&short_decl, ctx_b).unwrap();
+
+        // The two scopes import each other, one of them twice.
+        let (a, b) = (ctx_a.scope, ctx_b.scope);
+        a.import_scope(b, INVALID_SPAN, &sess).unwrap();
+        a.import_scope(b, INVALID_SPAN, &sess).unwrap();
+        b.import_scope(a, INVALID_SPAN, &sess).unwrap();
+        let found = a.resolve(short_decl.name.value.into(), true);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].value == Def2::Type(short));
+        let found = b.resolve(byte_decl.name.value.into(), true);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].value == Def2::Type(byte));
+        let bogus = get_name_table().intern("BOGUS", false);
+        assert!(a.resolve(bogus.into(), true).is_empty());
+    }
+
+    #[test]
+    fn dump_terminates_on_import_cycle() {
+        let sess = Session::new();
+        let arenas = Arenas2::new();
+        let ctx = AllocContext {
+            sess: &sess,
+            arenas: &arenas,
+            scope: arenas.alloc(ScopeData::root()),
+        };
+        let lib = Library::new(get_name_table().intern("magic", true), &[], ctx).unwrap();
+        let a: &ScopeData = arenas.alloc(ScopeData::new(lib.scope()));
+        let b: &ScopeData = arenas.alloc(ScopeData::new(lib.scope()));
+        let name = Spanned::new(get_name_table().intern("FOO", false).into(), INVALID_SPAN);
+        a.define(name, Def2::Lib(lib), &sess).unwrap();
+        a.import_scope(b, INVALID_SPAN, &sess).unwrap();
+        b.import_scope(a, INVALID_SPAN, &sess).unwrap();
+        let mut out = vec![];
+        a.dump(&mut out, 0).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("FOO: Lib("));
+        assert!(out.contains("(see above)"));
+    }
+
+    #[test]
+    fn alias_resolves_to_target() {
+        let type_name = get_name_table().intern("WORD", false);
+        let alias_name = get_name_table().intern("DATA", false);
+        let ident = |name| ast::PrimaryName {
+            id: ast::DUMMY_NODE_ID,
+            span: INVALID_SPAN,
+            kind: ast::PrimaryNameKind::Ident(name),
+        };
+        let type_decl = ast::TypeDecl {
+            id: ast::DUMMY_NODE_ID,
+            span: INVALID_SPAN,
+            name: Spanned::new(type_name, INVALID_SPAN),
+            data: None,
+        };
+        let alias_decl = ast::AliasDecl {
+            id: ast::DUMMY_NODE_ID,
+            span: INVALID_SPAN,
+            name: ident(alias_name),
+            subtype: None,
+            target: ast::CompoundName {
+                id: ast::DUMMY_NODE_ID,
+                span: INVALID_SPAN,
+                primary: ident(type_name),
+                parts: vec![],
+            },
+        };
+        let sess = Session::new();
+        let arenas = Arenas2::new();
+        let ctx = AllocContext {
+            sess: &sess,
+            arenas: &arenas,
+            scope: arenas.alloc(ScopeData::root()),
+        };
+        let lib = Library::new(get_name_table().intern("magic", true), &[], ctx).unwrap();
+        let ctx = AllocContext {
+            scope: arenas.alloc(ScopeData::new(lib.scope())),
+            ..ctx
+        };
+        let ty = TypeDecl2::alloc_slot(&type_decl, ctx).unwrap();
+        AliasDecl2::alloc_slot(&alias_decl, ctx).unwrap();
+        let found = ctx.scope.resolve(alias_name.into(), true);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].value == Def2::Type(ty));
+        assert!(ctx.scope.resolve(type_name.into(), true)[0].value == Def2::Type(ty));
+        assert_eq!(ctx.scope.name_of(&Def2::Type(ty)), Some(type_name.into()));
+        assert_eq!(ctx.scope.name_of(&Def2::Lib(lib)), None);
+
+        // Flattening a scope, or one that imports it, follows the alias too.
+        let alias_name = ResolvableName::from(alias_name);
+        assert!(ctx.scope.flatten()[&alias_name][0].value == Def2::Type(ty));
+        let user: &ScopeData = arenas.alloc(ScopeData::new(lib.scope()));
+        user.import_scope(ctx.scope, INVALID_SPAN, &sess).unwrap();
+        assert!(user.flatten()[&alias_name][0].value == Def2::Type(ty));
+    }
+
+    #[test]
+    fn repeated_import_not_ambiguous() {
+        let type_name = get_name_table().intern("WORD", false);
+        let type_decl = ast::TypeDecl {
+            id: ast::DUMMY_NODE_ID,
+            span: INVALID_SPAN,
+            name: Spanned::new(type_name, INVALID_SPAN),
+            data: None,
+        };
+        let sess = Session::new();
+        let arenas = Arenas2::new();
+        let ctx = AllocContext {
+            sess: &sess,
+            arenas: &arenas,
+            scope: arenas.alloc(ScopeData::root()),
+        };
+        let lib = Library::new(get_name_table().intern("magic", true), &[], ctx).unwrap();
+        let ctx = AllocContext {
+            scope: arenas.alloc(ScopeData::new(lib.scope())),
+            ..ctx
+        };
+        let ty = TypeDecl2::alloc_slot(&type_decl, ctx).unwrap();
+        let name = ResolvableName::from(type_name);
+        let def = Spanned::new(Def2::Type(ty), INVALID_SPAN);
+
+        // Importing `pkg.WORD` twice, as individual definition or selection.
+        let user = ScopeData::new(lib.scope());
+        user.import_def(name, def, &sess).unwrap();
+        user.import_def(name, def, &sess).unwrap();
+        user.import_defs(
+            vec![(Spanned::new(name, INVALID_SPAN), Def2::Type(ty))],
+            &sess,
+        );
+        let found = user.resolve(name, true);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].value == Def2::Type(ty));
+
+        let user = ScopeData::new(lib.scope());
+        user.import_scope_selective(ctx.scope, &[name], INVALID_SPAN, &sess)
+            .unwrap();
+        user.import_scope_selective(ctx.scope, &[name], INVALID_SPAN, &sess)
+            .unwrap();
+        let found = user.resolve(name, true);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].value == Def2::Type(ty));
+    }
+
+    /// A session that records the messages of all emitted diagnostics.
+    struct Recorder(RefCell<Vec<String>>);
+
+    impl DiagEmitter for Recorder {
+        fn emit(&self, diag: DiagBuilder2) {
+            self.0.borrow_mut().push(diag.get_message().clone());
+        }
+    }
+
+    impl SessionContext for Recorder {
+        fn has_verbosity(&self, verb: Verbosity) -> bool {
+            Verbosity::NAMES.contains(verb)
+        }
+    }
+
+    #[test]
+    fn import_defs_notes_names() {
+        let type_name = get_name_table().intern("WORD", false);
+        let type_decl = ast::TypeDecl {
+            id: ast::DUMMY_NODE_ID,
+            span: INVALID_SPAN,
+            name: Spanned::new(type_name, INVALID_SPAN),
+            data: None,
+        };
+        let sess = Session::new();
+        let arenas = Arenas2::new();
+        let ctx = AllocContext {
+            sess: &sess,
+            arenas: &arenas,
+            scope: arenas.alloc(ScopeData::root()),
+        };
+        let ty = TypeDecl2::alloc_slot(&type_decl, ctx).unwrap();
+        let name = ResolvableName::from(type_name);
+
+        // Importing at once notes the same names as importing one by one.
+        let single = Recorder(RefCell::new(vec![]));
+        ScopeData::root()
+            .import_def(name, Spanned::new(Def2::Type(ty), INVALID_SPAN), &single)
+            .unwrap();
+        let multi = Recorder(RefCell::new(vec![]));
+        ScopeData::root().import_defs(
+            vec![(Spanned::new(name, INVALID_SPAN), Def2::Type(ty))],
+            &multi,
+        );
+        assert_eq!(single.0.borrow().len(), 1);
+        assert_eq!(*single.0.borrow(), *multi.0.borrow());
+    }
+}
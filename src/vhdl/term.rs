@@ -21,7 +21,7 @@ use crate::arenas::Alloc;
 use crate::hir;
 use crate::konst::ConstInt;
 use crate::op::*;
-use crate::scope2::{Def2, ScopeData, TypeVariantDef};
+use crate::scope2::{ambiguity_error, Def2, ScopeData, TypeVariantDef};
 use crate::score::*;
 use crate::syntax::ast::{self, Dir};
 use crate::syntax::lexer::token::{Exponent, ExponentSign, Literal};
@@ -653,18 +653,8 @@ impl<'t, C: DiagEmitter, S> DefSpecificTermContext<'t, Def2<'t>> for TermContext
             }
             // TODO: Handle the function case.
             _ if !defs.is_empty() => {
-                let mut d =
-                    DiagBuilder2::error(format!("`{}` is ambiguous", name.value)).span(name.span);
-                d = d.add_note("Found the following definitions:");
-                if first_def.span() != INVALID_SPAN {
-                    d = d.span(first_def.span());
-                }
-                for def in defs {
-                    if def.span() != INVALID_SPAN {
-                        d = d.span(def.span());
-                    }
-                }
-                self.emit(d);
+                defs.push(first_def);
+                self.emit(ambiguity_error(name, &defs));
                 return Err(());
             }
             _ => Term::Ident2(first_def),
@@ -735,6 +725,7 @@ impl<'t> ScopeSpecificTermContext<'t, &'t ScopeData<'t>, Def2<'t>>
             match def {
                 Def2::Lib(x) => Some(x.scope()),
                 Def2::Pkg(x) => x.poll().ok().map(|x| x.scope()),
+                Def2::PkgInst(x) => Some(x.scope()),
                 _ => None,
             }
         } else {
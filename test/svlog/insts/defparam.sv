@@ -0,0 +1,17 @@
+// RUN: moore %s -e A
+
+// Overrides apply only to the named instance, and are evaluated in the
+// environment of the surrounding module.
+module A #(parameter int N = 4);
+    B b0();
+    B b1();
+    defparam b1.W = N * 4;
+endmodule
+
+// CHECK: entity @B.param1 () -> () {
+// CHECK: %x = sig i8 %0
+// CHECK: entity @B.param2 () -> () {
+// CHECK: %x = sig i16 %0
+module B #(parameter int W = 8);
+    logic [W-1:0] x;
+endmodule
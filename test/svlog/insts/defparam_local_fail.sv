@@ -0,0 +1,12 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B b();
+    // CHECK-ERR: error: value parameter `L` cannot be overridden
+    defparam b.L = 4;
+endmodule
+
+module B #(parameter int W = 8);
+    localparam int L = W / 2;
+endmodule
@@ -0,0 +1,11 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B b();
+    // CHECK-ERR: error: module `B` has no parameter `X`
+    defparam b.X = 4;
+endmodule
+
+module B #(parameter int W = 8);
+endmodule
@@ -0,0 +1,17 @@
+// RUN: moore %s -e A
+
+// Instances in the branch of an if-generate that is not selected are not
+// elaborated, such that their invalid parameter assignments go unnoticed.
+module A;
+    B #(0) b();
+endmodule
+
+module B #(int EN);
+    if (EN)
+        C #(.X(1)) c();
+    else
+        C c();
+endmodule
+
+module C;
+endmodule
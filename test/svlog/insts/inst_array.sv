@@ -0,0 +1,17 @@
+// RUN: moore %s -e A
+
+// All elements of an instance array share the parameter assignments.
+module A;
+    B #(.W(4)) b[3:0]();
+endmodule
+
+// CHECK: entity @B.param1 () -> () {
+// CHECK: %x = sig i4 %0
+// CHECK: entity @A () -> () {
+// CHECK: inst @B.param1 () -> ()
+// CHECK: inst @B.param1 () -> ()
+// CHECK: inst @B.param1 () -> ()
+// CHECK: inst @B.param1 () -> ()
+module B #(parameter int W = 1);
+    logic [W-1:0] x;
+endmodule
@@ -0,0 +1,11 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    logic [3:0] bus;
+    // CHECK-ERR: error: unsupported: port connections of instance array `b`
+    B b[3:0](.x(bus));
+endmodule
+
+module B (input logic x);
+endmodule
@@ -0,0 +1,11 @@
+// RUN: moore %s -e A --max-inst-depth 10
+// FAIL
+
+module A;
+    B b();
+endmodule
+
+module B;
+    B b();
+    // CHECK-ERR: error: instantiation recursion limit exceeded, possible cyclic instantiation
+endmodule
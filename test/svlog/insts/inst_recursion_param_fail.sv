@@ -0,0 +1,14 @@
+// RUN: moore %s -e A --max-inst-depth 4
+// FAIL
+
+module A;
+    B #(.N(0)) b();
+endmodule
+
+module B #(parameter int N = 0);
+    B #(.N(N + 1)) b();
+    // CHECK-ERR: error: instantiation recursion limit exceeded, possible cyclic instantiation
+    // CHECK-ERR: = note: Instantiations are limited to a depth of 4
+    // CHECK-ERR: = note: Innermost instantiation has parameters: N=4
+    // CHECK-ERR: = note: Through instance `b` here:
+endmodule
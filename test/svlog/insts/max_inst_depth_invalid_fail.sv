@@ -0,0 +1,6 @@
+// RUN: moore %s -e A --max-inst-depth foo
+// FAIL
+// CHECK-ERR: error: Invalid value for '--max-inst-depth <DEPTH>': `foo` is not a valid depth
+
+module A;
+endmodule
@@ -0,0 +1,20 @@
+// RUN: moore %s -e A
+
+// The defaults of typed parameters are checked against the declared type once
+// per module, using the same conversion rules as parameter assignments.
+module A;
+    B b();
+endmodule
+
+module B #(
+    parameter real R = 0,
+    parameter int I = R,
+    parameter logic [7:0] W = "a"
+);
+endmodule
+
+// CHECK: entity @B.param1 () -> () {
+// CHECK: }
+// CHECK: entity @A () -> () {
+// CHECK:     inst @B.param1 () -> ()
+// CHECK: }
@@ -0,0 +1,10 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B b();
+endmodule
+
+module B #(parameter real R = 0, parameter string S = R);
+    // CHECK-ERR: error: default of type `real` cannot be assigned to value parameter `S` of type `string`
+endmodule
@@ -0,0 +1,11 @@
+// RUN: moore %s -e A
+// Parameter defaults may refer to earlier parameters.
+
+module A;
+    B b0();
+    B #(.W(4)) b1();
+endmodule
+
+module B #(parameter int W = 8, parameter type T = logic [W-1:0]);
+    T x;
+endmodule
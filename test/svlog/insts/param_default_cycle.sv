@@ -0,0 +1,12 @@
+// RUN: moore %s -e A
+
+// Assigning the parameter explicitly breaks the cycle.
+module A;
+    B #(.W(8)) b0();
+endmodule
+
+// CHECK: entity @B.param1 () -> () {
+// CHECK: %x = sig i4 %0
+module B #(parameter int W = W + 1, parameter int D = W / 2);
+    logic [D-1:0] x;
+endmodule
@@ -0,0 +1,12 @@
+// RUN: moore %s -e A
+// FAIL
+
+// Parameters must be declared before they are used, such that two defaults
+// cannot refer to each other. The forward reference is reported instead.
+module A;
+    B b();
+endmodule
+
+module B #(parameter int W = D * 2, parameter int D = W / 2);
+    // CHECK-ERR: error: `D` not found
+endmodule
@@ -0,0 +1,10 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B b();
+endmodule
+
+module B #(parameter int W = 8, parameter int D);
+    // CHECK-ERR: error: parameter `D` has no default and was not assigned
+endmodule
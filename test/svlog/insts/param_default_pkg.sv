@@ -0,0 +1,34 @@
+// RUN: moore %s -e A
+
+package cfg_pkg;
+    localparam int DEFAULT_WIDTH = 8;
+    localparam int DEFAULT_DEPTH = DEFAULT_WIDTH * 2;
+endpackage
+
+module A;
+    B b0();
+    C c0();
+    C #(.W(4)) c1();
+endmodule
+
+// Scoped reference to a package constant.
+// CHECK: entity @B.param1 () -> () {
+// CHECK: %x = sig i8 %0
+module B #(parameter int W = cfg_pkg::DEFAULT_WIDTH);
+    logic [W-1:0] x;
+endmodule
+
+// Reference through a wildcard import in the module header.
+// CHECK: entity @C.param2 () -> () {
+// CHECK: %x = sig i8 %0
+// CHECK: %y = sig i24 %1
+// CHECK: entity @C.param3 () -> () {
+// CHECK: %x = sig i4 %0
+// CHECK: %y = sig i20 %1
+module C import cfg_pkg::*; #(
+    parameter int W = DEFAULT_WIDTH,
+    parameter int D = DEFAULT_DEPTH + W
+);
+    logic [W-1:0] x;
+    logic [D-1:0] y;
+endmodule
@@ -0,0 +1,23 @@
+// RUN: moore %s -e A
+
+// Defaults that refer to package constants are resolved in the scope of the
+// declaring module, even if the instantiating module declares a constant of
+// the same name.
+
+package cfg_pkg;
+    localparam int DEFAULT_WIDTH = 8;
+endpackage
+
+module A;
+    localparam int DEFAULT_WIDTH = 2;
+    logic [11:0] y;
+    B #(.W(4)) b(y);
+endmodule
+
+module B import cfg_pkg::*; #(
+    parameter int W = 1,
+    parameter int D = DEFAULT_WIDTH + W
+) (output logic [D-1:0] y);
+endmodule
+
+// CHECK: entity @B.param1 () -> (i12$ %y) {
@@ -0,0 +1,10 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B b();
+endmodule
+
+module B #(parameter int W = W + 1);
+    // CHECK-ERR: error: cyclic parameter defaults: `W` -> `W`
+endmodule
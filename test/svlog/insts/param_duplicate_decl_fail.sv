@@ -0,0 +1,10 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B b();
+endmodule
+
+module B #(parameter int W = 1, parameter int W = 2);
+    // CHECK-ERR: error: parameter `W` declared multiple times
+endmodule
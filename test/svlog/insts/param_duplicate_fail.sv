@@ -0,0 +1,10 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B #(.W(8), .W(16)) b0();
+    // CHECK-ERR: error: value parameter `W` assigned multiple times
+endmodule
+
+module B #(parameter int W = 1);
+endmodule
@@ -0,0 +1,10 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B #(4, .W(16)) b1();
+    // CHECK-ERR: error: value parameter `W` assigned multiple times
+endmodule
+
+module B #(parameter int W = 1);
+endmodule
@@ -0,0 +1,11 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B #(1, 2, 3, 4) b();
+    // CHECK-ERR: error: positional parameter #3 exceeds the 2 declared parameters
+    // CHECK-ERR: error: positional parameter #4 exceeds the 2 declared parameters
+endmodule
+
+module B #(parameter int W = 1, parameter int D = 2);
+endmodule
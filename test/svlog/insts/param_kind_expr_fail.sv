@@ -0,0 +1,10 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B #(.T(42)) b();
+    // CHECK-ERR: error: expression assigned to type parameter `T`
+endmodule
+
+module B #(parameter type T = logic);
+endmodule
@@ -0,0 +1,10 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B #(logic [7:0]) b();
+    // CHECK-ERR: error: type assigned to value parameter `W`
+endmodule
+
+module B #(parameter int W = 1);
+endmodule
@@ -0,0 +1,15 @@
+// RUN: moore %s -e A -Vparams
+
+// Overriding a parameter points out the localparams derived from it, including
+// the ones that only refer to it through another localparam.
+module A;
+    B #(.W(16)) b();
+    // CHECK-ERR: note: overridden parameter has derived localparams
+    // CHECK-ERR: = note: Overriding `W` also recomputes localparam `BYTES`, `MASK`
+endmodule
+
+module B #(parameter int W = 8, parameter int D = 4);
+    localparam int BYTES = W / 8;
+    localparam int MASK = (1 << BYTES) - 1;
+    localparam int DEPTH = D * 2;
+endmodule
@@ -0,0 +1,10 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B #(.L(8)) b();
+    // CHECK-ERR: error: value parameter `L` cannot be overridden
+endmodule
+
+module B #(parameter int W = 1, localparam int L = 2);
+endmodule
@@ -0,0 +1,10 @@
+// RUN: moore %s -e A
+
+// Local parameters do not count towards the index of positional assignments.
+// The `logic` below would otherwise be assigned to the value parameter `L`.
+module A;
+    B #(bit, logic) b();
+endmodule
+
+module B #(parameter type T = int, localparam int L = 2, parameter type U = int);
+endmodule
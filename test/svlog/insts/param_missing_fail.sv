@@ -0,0 +1,10 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B #(.D(4)) b();
+    // CHECK-ERR: error: parameters `W`, `T` have no default and were not assigned
+endmodule
+
+module B #(parameter int W, parameter int D, parameter type T);
+endmodule
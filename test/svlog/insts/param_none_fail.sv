@@ -0,0 +1,12 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    logic x, y;
+    B #(x, y) b();
+    // CHECK-ERR: error: module `B` declares no parameters; did you mean to connect ports?
+    // CHECK-ERR: error: module `B` declares no parameters; did you mean to connect ports?
+endmodule
+
+module B (input logic a, input logic b);
+endmodule
@@ -0,0 +1,10 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B #(.W(8), 16) b();
+    // CHECK-ERR: error: positional parameters must appear before named ones
+endmodule
+
+module B #(parameter int W = 1, parameter int D = 2);
+endmodule
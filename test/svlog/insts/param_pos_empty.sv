@@ -0,0 +1,14 @@
+// RUN: moore %s -e A
+
+// An empty assignment keeps the default of its parameter. An empty positional
+// assignment does not shift the ones that follow it, which would otherwise
+// assign `16` to the type parameter `T`.
+module A;
+    B #(8, , 16) b0();
+    B #(.X(8), .T(), .Z(16)) b1();
+endmodule
+
+module B #(parameter int X = 1, parameter type T = logic [X-1:0], parameter int Z = 3);
+    T x;
+    logic [Z-1:0] z;
+endmodule
@@ -0,0 +1,10 @@
+// RUN: moore %s -e A
+
+module A;
+    typedef enum { RED, GREEN } color_t;
+    typedef struct packed { logic a, b; } pair_t;
+    B #(color_t, pair_t) b();
+endmodule
+
+module B #(parameter type enum E, parameter type struct S);
+endmodule
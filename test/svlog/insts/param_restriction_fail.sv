@@ -0,0 +1,10 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B #(.E(logic [3:0])) b();
+    // CHECK-ERR: error: type parameter `E` requires an enum type, but `logic [3:0]` is not an enum
+endmodule
+
+module B #(parameter type enum E);
+endmodule
@@ -0,0 +1,16 @@
+// RUN: moore %s -e A
+
+// Type parameters bound to the type of an expression
+
+module A;
+    logic [7:0] some_wire;
+    B #(.T(type(some_wire))) b(some_wire);
+endmodule
+
+module B #(type T = bit) (input T t);
+endmodule
+
+// CHECK: entity @B.param1 (i8$ %t) -> () {
+// CHECK: }
+// CHECK:
+// CHECK: entity @A () -> () {
@@ -0,0 +1,11 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    logic [7:0] some_wire;
+    B #(.W(type(some_wire))) b();
+    // CHECK-ERR: error: type reference assigned to value parameter `W`
+endmodule
+
+module B #(parameter int W = 1);
+endmodule
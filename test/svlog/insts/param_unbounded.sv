@@ -0,0 +1,14 @@
+// RUN: moore %s -e A
+
+module A;
+    B #(.N($)) b0();
+    B #(.N(8)) b1();
+endmodule
+
+module B #(parameter int N = $);
+    if ($isunbounded(N)) begin : g_unbounded
+        logic [7:0] x;
+    end else begin : g_bounded
+        logic [N-1:0] x;
+    end
+endmodule
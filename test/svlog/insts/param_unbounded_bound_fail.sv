@@ -0,0 +1,12 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B #(.N($)) b();
+endmodule
+
+module B #(parameter int N = 4);
+    // CHECK-ERR: error: array bound `N` is unbounded
+    // CHECK-ERR: = note: Parameter value assigned here:
+    logic x [N];
+endmodule
@@ -0,0 +1,12 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B #(.N($)) b();
+endmodule
+
+module B #(parameter int N = 4);
+    localparam int M = N + 1;
+    // CHECK-ERR: error: unbounded value `N` cannot be used in an expression
+    logic [M-1:0] x;
+endmodule
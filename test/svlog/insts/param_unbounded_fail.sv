@@ -0,0 +1,10 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B #(.S($)) b();
+    // CHECK-ERR: error: `$` cannot be assigned to value parameter `S` of type `string`
+endmodule
+
+module B #(parameter string S = "foo");
+endmodule
@@ -0,0 +1,11 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B #(.width(8)) b();
+    // CHECK-ERR: error: no parameter `width` in module `B`
+    // CHECK-ERR: = note: parameter names are case-sensitive; did you mean `WIDTH`?
+endmodule
+
+module B #(parameter int WIDTH = 1);
+endmodule
@@ -0,0 +1,11 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B #(.X(1)) b();
+    // CHECK-ERR: error: no parameter `X` in module `B`
+    // CHECK-ERR: = note: declared parameters are `W`, `T`, `D`, `N`
+endmodule
+
+module B #(parameter int W = 1, parameter type T = logic, parameter int D = 2, parameter int N = 3);
+endmodule
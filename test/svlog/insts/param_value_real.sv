@@ -0,0 +1,21 @@
+// RUN: moore %s -e A
+
+// Reals and integral values may be assigned to each other's parameters, in
+// which case they are converted implicitly.
+module A #(parameter real R = 2.5);
+    B #(R) b0();
+    C #(42) c0();
+    C #(R) c1();
+endmodule
+
+module B #(parameter logic [7:0] W = 0);
+endmodule
+
+module C #(parameter real X = 0.0);
+endmodule
+
+// CHECK: entity @A () -> () {
+// CHECK:     inst @B.param1 () -> ()
+// CHECK:     inst @C.param2 () -> ()
+// CHECK:     inst @C.param3 () -> ()
+// CHECK: }
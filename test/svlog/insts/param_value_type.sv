@@ -0,0 +1,12 @@
+// RUN: moore %s -e A
+
+// Values may be assigned to parameters of a different type, as long as they
+// can be converted implicitly.
+module A;
+    B #(42) b0();
+    B #(4'b1010) b1();
+    B #("a") b2();
+endmodule
+
+module B #(parameter logic [7:0] W = 0);
+endmodule
@@ -0,0 +1,10 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A #(parameter real R = 0);
+    B #(R) b();
+    // CHECK-ERR: error: value of type `real` cannot be assigned to value parameter `S` of type `string`
+endmodule
+
+module B #(parameter string S = "");
+endmodule
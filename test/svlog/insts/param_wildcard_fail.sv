@@ -0,0 +1,10 @@
+// RUN: moore %s -e A
+// FAIL
+
+module A;
+    B #(.*) b();
+    // CHECK-ERR: error: `.*` is not allowed in parameter overrides
+endmodule
+
+module B #(parameter int W = 1);
+endmodule
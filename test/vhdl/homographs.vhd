@@ -0,0 +1,8 @@
+-- RUN: moore --syntax --emit-pkgs %s
+-- Enumeration literals of distinct types may share a name, since their
+-- profiles differ.
+package pkg is
+	type LEVEL is (LOW, HIGH);
+	type DRIVE is (LOW, HIGH, WEAK);
+	alias L is LOW;
+end package;
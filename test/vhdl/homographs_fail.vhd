@@ -0,0 +1,14 @@
+-- RUN: moore --syntax --emit-pkgs %s
+-- FAIL
+-- Declarations that are homographs of each other may not share a scope.
+package pkg is
+	type LEVEL is (LOW, HIGH, LOW);
+	-- CHECK-ERR: error: `LOW` has already been declared
+	type HIGH is range 0 to 1;
+	-- CHECK-ERR: error: `HIGH` has already been declared
+	type LEVEL is range 0 to 1;
+	-- CHECK-ERR: error: `LEVEL` has already been declared
+	function F (A : work.pkg.LEVEL) return BIT;
+	function F (B : work.pkg.LEVEL) return BIT;
+	-- CHECK-ERR: error: `F` has already been declared
+end package;
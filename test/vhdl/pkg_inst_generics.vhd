@@ -0,0 +1,19 @@
+-- RUN: moore --syntax --emit-pkgs %s
+
+package gen_pkg is
+	generic (
+		width : integer;
+		depth : integer := 4
+	);
+	constant size : integer := width * depth;
+	type word is range 0 to 255;
+end;
+
+package inst_pkg is
+	package p1 is new gen_pkg generic map (8);
+	package p2 is new gen_pkg generic map (width => 8, depth => 2);
+	package p3 is new work.gen_pkg generic map (16, depth => 1);
+	-- The declarations of an instance can be selected by its name.
+	alias w1 is p1.word;
+	alias w3 is p3.word;
+end;
@@ -0,0 +1,30 @@
+-- RUN: moore --syntax --emit-pkgs %s
+-- FAIL
+
+package gen_pkg is
+	generic (
+		width : integer;
+		depth : integer := 4
+	);
+	constant size : integer := width * depth;
+end;
+
+package inst_pkg is
+	package p1 is new gen_pkg generic map (depth => 2);
+	package p2 is new gen_pkg generic map (width => 8, size => 2);
+	package p3 is new gen_pkg generic map (8, 2, 1);
+	package p4 is new gen_pkg generic map (8, width => 4);
+end;
+
+-- CHECK-ERR: error: generic `width` not associated and has no default
+-- CHECK-ERR: error: no generic `size` in package
+-- CHECK-ERR: error: superfluous generic association 3
+-- CHECK-ERR: error: generic `width` associated multiple times
+
+package dup_pkg is
+	package p1 is new gen_pkg generic map (8);
+	type p1 is range 0 to 1;
+	-- CHECK-ERR: error: `p1` has already been declared
+	alias a is p1.missing;
+	-- CHECK-ERR: error: `missing` is unknown
+end;
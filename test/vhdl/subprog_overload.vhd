@@ -0,0 +1,15 @@
+-- RUN: moore --syntax --emit-pkgs %s
+-- Subprograms with distinct parameter and result type profiles may share a
+-- name, and may also share a name with enumeration literals.
+package pkg is
+	type LEVEL is (LOW, HIGH);
+	function HIGH return BIT;
+	function MAX (A, B : INTEGER) return INTEGER;
+	function MAX (A, B : BIT) return BIT;
+	procedure RESET (signal S : out BIT);
+	procedure RESET (signal S : out INTEGER);
+	-- Selected type marks are distinguished by all of their names.
+	function F (A : work.pkg.LEVEL) return BIT;
+	function F (A : work.other.LEVEL) return BIT;
+	function G (A : BIT_VECTOR(7 downto 0)) return BIT;
+end package;
@@ -0,0 +1,24 @@
+-- RUN: moore --syntax --emit-pkgs %s
+-- FAIL
+-- A name that is made visible by multiple use clauses is ambiguous, and all
+-- candidates are listed.
+package pkg_a is
+	package inner is
+		type T is range 0 to 255;
+	end package;
+end package;
+
+package pkg_b is
+	package inner is
+		type T is range 0 to 65535;
+	end package;
+end package;
+
+package pkg_c is
+	use work.pkg_a.all;
+	use work.pkg_b.all;
+	use inner.T;
+	-- CHECK-ERR: error: `inner` is ambiguous
+	-- CHECK-ERR: = note: Candidate declared here:
+	-- CHECK-ERR: = note: Candidate declared here:
+end package;
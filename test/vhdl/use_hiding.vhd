@@ -0,0 +1,37 @@
+-- RUN: moore --syntax --emit-pkgs %s
+-- Directly visible declarations hide names imported by use clauses, and names
+-- imported individually hide names imported through `all`. The hidden imports
+-- are never used.
+package pkg_a is
+	type T is range 0 to 255;
+	type U is range 0 to 255;
+end package;
+
+package pkg_b is
+	type T is range 0 to 65535;
+	type U is range 0 to 65535;
+end package;
+
+-- A local declaration hides an individually imported name.
+package pkg_local_over_selected is
+	use work.pkg_a.T;
+	-- CHECK-ERR: warning: `T` is imported but never used
+	type T is range 0 to 15;
+	alias K is T;
+end package;
+
+-- A local declaration hides a name imported through `all`.
+package pkg_local_over_all is
+	use work.pkg_a.all;
+	-- CHECK-ERR: warning: nothing is used from `work.pkg_a.all`
+	type T is range 0 to 15;
+	alias K is T;
+end package;
+
+-- An individually imported name hides a name imported through `all`.
+package pkg_selected_over_all is
+	use work.pkg_a.all;
+	-- CHECK-ERR: warning: nothing is used from `work.pkg_a.all`
+	use work.pkg_b.T;
+	alias K is T;
+end package;
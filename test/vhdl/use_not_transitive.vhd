@@ -0,0 +1,20 @@
+-- RUN: moore --syntax --emit-pkgs %s
+-- FAIL
+-- Use clauses are not transitive. The names made visible in a package by its
+-- own use clauses are not visible to the users of that package.
+package pkg_c is
+	package inner is
+		type T is range 0 to 255;
+	end package;
+end package;
+
+package pkg_b is
+	use work.pkg_c.all;
+	use inner.T;
+end package;
+
+package pkg_a is
+	use work.pkg_b.all;
+	use inner.T;
+	-- CHECK-ERR: error: `inner` is unknown
+end package;
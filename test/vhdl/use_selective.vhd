@@ -0,0 +1,15 @@
+-- RUN: moore --syntax --emit-pkgs %s
+-- FAIL
+
+package pkg_a is
+	type BYTE is range 0 to 255;
+	type SHORT is range 0 to 65535;
+end package;
+
+package pkg_b is
+	use work.pkg_a.BYTE;
+	alias B is BYTE;
+	-- Only `BYTE` is visible here, `SHORT` is not.
+	alias S is SHORT;
+	-- CHECK-ERR: error: `SHORT` is unknown
+end package;
@@ -0,0 +1,12 @@
+-- RUN: moore --syntax --emit-pkgs %s
+-- FAIL
+-- Unknown names in use clauses come with a suggestion for a similar name.
+package pkg_a is
+	type BYTE is range 0 to 255;
+end package;
+
+package pkg_b is
+	use work.pkg_a.BITE;
+	-- CHECK-ERR: error: `BITE` is unknown
+	-- CHECK-ERR: = note: Did you mean `BYTE`?
+end package;
@@ -0,0 +1,18 @@
+-- RUN: moore --syntax --emit-pkgs %s
+-- Making the same declaration visible through multiple use clauses does not
+-- render it ambiguous.
+package pkg_a is
+	type T is range 0 to 255;
+end package;
+
+package pkg_b is
+	use work.pkg_a.T;
+	use work.pkg_a.T;
+	alias A is T;
+end package;
+
+package pkg_c is
+	use work.pkg_a.all;
+	use work.pkg_a.all;
+	alias A is T;
+end package;
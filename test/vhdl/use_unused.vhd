@@ -0,0 +1,15 @@
+-- RUN: moore --syntax --emit-pkgs %s
+-- Use clauses that never contribute to resolving a name are reported.
+package pkg_a is
+	type BYTE is range 0 to 255;
+	type SHORT is range 0 to 65535;
+end package;
+
+package pkg_b is
+	use work.pkg_a.BYTE;
+	use work.pkg_a.SHORT;
+	-- CHECK-ERR: warning: `SHORT` is imported but never used
+	use work.pkg_a.all;
+	-- CHECK-ERR: warning: nothing is used from `work.pkg_a.all`
+	alias B is BYTE;
+end package;